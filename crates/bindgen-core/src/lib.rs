@@ -439,9 +439,14 @@ mod tests {
 }
 
 pub trait WorldGenerator {
-    fn generate(&mut self, resolve: &Resolve, id: WorldId, files: &mut Files) {
+    fn generate(
+        &mut self,
+        resolve: &Resolve,
+        id: WorldId,
+        files: &mut Files,
+    ) -> anyhow::Result<()> {
         let world = &resolve.worlds[id];
-        self.preprocess(resolve, &world.name);
+        self.preprocess(resolve, id)?;
 
         let mut funcs = Vec::new();
         for (name, import) in world.imports.iter() {
@@ -470,12 +475,13 @@ pub trait WorldGenerator {
         if !funcs.is_empty() {
             self.export_funcs(resolve, id, &funcs, files);
         }
-        self.finish(resolve, id, files);
+        self.finish(resolve, id, files)
     }
 
-    fn preprocess(&mut self, resolve: &Resolve, name: &str) {
+    fn preprocess(&mut self, resolve: &Resolve, world: WorldId) -> anyhow::Result<()> {
         drop(resolve);
-        drop(name);
+        drop(world);
+        Ok(())
     }
 
     fn import_interface(
@@ -513,7 +519,12 @@ pub trait WorldGenerator {
         types: &[(&str, TypeId)],
         files: &mut Files,
     );
-    fn finish(&mut self, resolve: &Resolve, world: WorldId, files: &mut Files);
+    fn finish(
+        &mut self,
+        resolve: &Resolve,
+        world: WorldId,
+        files: &mut Files,
+    ) -> anyhow::Result<()>;
 }
 
 /// This is a possible replacement for the `Generator` trait above, currently
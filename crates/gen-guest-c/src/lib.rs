@@ -68,9 +68,10 @@ enum Scalar {
 }
 
 impl WorldGenerator for C {
-    fn preprocess(&mut self, resolve: &Resolve, name: &str) {
-        self.world = name.to_string();
+    fn preprocess(&mut self, resolve: &Resolve, world: WorldId) -> anyhow::Result<()> {
+        self.world = resolve.worlds[world].name.clone();
         self.sizes.fill(resolve);
+        Ok(())
     }
 
     fn import_interface(
@@ -182,7 +183,7 @@ impl WorldGenerator for C {
         gen.gen.src.append(&gen.src);
     }
 
-    fn finish(&mut self, resolve: &Resolve, id: WorldId, files: &mut Files) {
+    fn finish(&mut self, resolve: &Resolve, id: WorldId, files: &mut Files) -> anyhow::Result<()> {
         let world = &resolve.worlds[id];
         let linking_symbol = component_type_object::linking_symbol(&world.name);
         self.include("<stdlib.h>");
@@ -349,10 +350,9 @@ impl WorldGenerator for C {
         files.push(&format!("{snake}.h"), h_str.as_bytes());
         files.push(
             &format!("{snake}_component_type.o",),
-            component_type_object::object(resolve, id, self.opts.string_encoding)
-                .unwrap()
-                .as_slice(),
+            component_type_object::object(resolve, id, self.opts.string_encoding)?.as_slice(),
         );
+        Ok(())
     }
 }
 
@@ -3,6 +3,7 @@ use std::collections::{HashMap, HashSet};
 use std::fmt::Write as _;
 use std::io::{Read, Write};
 use std::mem;
+use std::path::PathBuf;
 use std::process::{Command, Stdio};
 use wit_bindgen_core::wit_parser::abi::{AbiVariant, Bindgen, Instruction, LiftLower, WasmType};
 use wit_bindgen_core::{
@@ -13,6 +14,7 @@ use wit_bindgen_gen_rust_lib::{
     int_repr, to_rust_ident, wasm_type, FnSig, RustFlagsRepr, RustFunctionGenerator, RustGenerator,
     TypeMode,
 };
+use wit_component::StringEncoding;
 
 #[derive(Default)]
 struct RustWasm {
@@ -21,7 +23,110 @@ struct RustWasm {
     opts: Opts,
     exports: Vec<Source>,
     skip: HashSet<String>,
+    acronyms: HashSet<String>,
+    prelude: Vec<(String, String)>,
     interface_names: HashMap<InterfaceId, String>,
+    symbols: SymbolMap,
+    /// Whether the `rt` items used by generated code have been imported into
+    /// the root of the world, which is shared by several generators.
+    root_imports_rt: bool,
+}
+
+/// The core wasm symbols of the bindings, emitted with `emit_symbol_map`.
+#[derive(Default)]
+struct SymbolMap {
+    imports: Vec<ImportSymbol>,
+    exports: Vec<ExportSymbol>,
+}
+
+struct ImportSymbol {
+    module: String,
+    name: String,
+    path: String,
+}
+
+struct ExportSymbol {
+    name: String,
+    post_return: Option<String>,
+    path: String,
+}
+
+impl SymbolMap {
+    fn to_json(&self) -> String {
+        let mut json = String::from("{\n  \"imports\": [");
+        for (i, import) in self.imports.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            uwrite!(
+                json,
+                "\n    {{\"module\": {}, \"name\": {}, \"path\": {}}}",
+                json_string(&import.module),
+                json_string(&import.name),
+                json_string(&import.path),
+            );
+        }
+        json.push_str("\n  ],\n  \"exports\": [");
+        for (i, export) in self.exports.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            let post_return = match &export.post_return {
+                Some(name) => json_string(name),
+                None => "null".to_string(),
+            };
+            uwrite!(
+                json,
+                "\n    {{\"name\": {}, \"post_return\": {post_return}, \"path\": {}}}",
+                json_string(&export.name),
+                json_string(&export.path),
+            );
+        }
+        json.push_str("\n  ]\n}\n");
+        json
+    }
+
+    /// Checks that no two core wasm exports share a name, which the linker
+    /// would otherwise report as a duplicate symbol without saying where
+    /// either came from.
+    ///
+    /// `others` are the exports which aren't generated for a WIT function,
+    /// along with a description of each.
+    fn check_export_collisions(&self, others: Vec<(String, String)>) -> anyhow::Result<()> {
+        let mut seen = HashMap::new();
+        let exports = self.exports.iter().flat_map(|export| {
+            let post_return = export.post_return.as_ref().map(|name| {
+                (
+                    name.clone(),
+                    format!("the post-return function of `{}`", export.path),
+                )
+            });
+            [(export.name.clone(), format!("`{}`", export.path))]
+                .into_iter()
+                .chain(post_return)
+        });
+        for (name, what) in others.into_iter().chain(exports) {
+            if let Some(prev) = seen.get(&name) {
+                anyhow::bail!("core wasm export `{name}` is generated for both {prev} and {what}");
+            }
+            seen.insert(name, what);
+        }
+        Ok(())
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut ret = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            c if c.is_control() => uwrite!(ret, "\\u{:04x}", c as u32),
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
 }
 
 #[derive(Default, Debug, Clone)]
@@ -37,9 +142,22 @@ pub struct Opts {
     pub unchecked: bool,
 
     /// If true, code generation should avoid any features that depend on `std`.
+    ///
+    /// Interface modules then import `String` and `Vec` from `alloc`, while
+    /// bindings generated at the root of the world use the ones in scope
+    /// where the bindings are generated.
     #[cfg_attr(feature = "clap", arg(long))]
     pub no_std: bool,
 
+    /// If true, `impl std::error::Error` for error types is emitted behind
+    /// `#[cfg(feature = "std")]`, so a `no_std` crate can still provide it
+    /// when its own `std` feature is enabled.
+    ///
+    /// This takes precedence over `no_std` for these impls; `Display` impls
+    /// only need `core` and are always emitted.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub std_feature: bool,
+
     /// If true, adds `#[macro_export]` to the `export_*!` macro generated to
     /// export it from the Rust crate.
     #[cfg_attr(feature = "clap", arg(long))]
@@ -70,15 +188,786 @@ pub struct Opts {
     /// Names of functions to skip generating bindings for.
     #[cfg_attr(feature = "clap", arg(long))]
     pub skip: Vec<String>,
+
+    /// If true, records whose only field is a `list<T>` implement
+    /// `IntoIterator` (both by value and by reference) by delegating to that
+    /// list.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub impl_into_iterator: bool,
+
+    /// If true, enums used as the error type of a `result` get a
+    /// `to_errno(&self) -> i32` method returning a negative error code based
+    /// on the order of the enum's cases.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub c_error_codes: bool,
+
+    /// If true, enums are always lifted with a `match` on the discriminant,
+    /// even in `unchecked` mode, instead of transmuting the raw value.
+    ///
+    /// This guarantees that an out-of-range discriminant from the host is
+    /// never undefined behavior, at the cost of a branch.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub safe_enum_lift: bool,
+
+    /// Path to a module used in place of `alloc::alloc` for the temporary
+    /// allocations made while lowering arguments to imported functions.
+    ///
+    /// The module must provide `Layout`, `alloc`, `dealloc` and
+    /// `handle_alloc_error` with the same signatures as `alloc::alloc`. Memory
+    /// handed out by the canonical ABI, such as returned lists, is still
+    /// allocated through `cabi_realloc`.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub allocator_path: Option<String>,
+
+    /// If true, the functions defined by the `export_*!` macro are only
+    /// compiled for `wasm32` targets.
+    ///
+    /// This allows a guest crate to be built and linked natively, for example
+    /// by `cargo test` on the host, without defining symbols that assume the
+    /// wasm ABI.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub native_export_stubs: bool,
+
+    /// If true, imported functions are only bound to their wasm imports on
+    /// `wasm32` targets, and on other targets panic when called.
+    ///
+    /// This allows a guest crate to be linked natively without providing
+    /// definitions for every import.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub native_import_trap: bool,
+
+    /// If set, lifted lists and strings longer than this many elements trap
+    /// with an error message instead of being allocated.
+    ///
+    /// This protects guests which don't trust their imports from a huge
+    /// length exhausting memory.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub max_list_len: Option<usize>,
+
+    /// Words which are uppercased wholesale when converting WIT names into
+    /// Rust type and trait names, for example `json` to turn `json-rpc` into
+    /// `JSONRpc` rather than `JsonRpc`.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub acronyms: Vec<String>,
+
+    /// If true, the `export_*!` macro additionally defines a
+    /// `#[wasm_bindgen]` function for each export which forwards to the
+    /// export trait, so the same crate can be built with `wasm-bindgen` for
+    /// the web.
+    ///
+    /// The crate must depend on `wasm-bindgen`, and only exports whose
+    /// parameters and result are scalars or strings are supported.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub wasm_bindgen_compat: bool,
+
+    /// If true, a `prelude` module is generated which re-exports the export
+    /// traits of the world along with the types used in their signatures, for
+    /// use as `use bindings::prelude::*;` when implementing exports.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub prelude: bool,
+
+    /// The visibility of the generated module for each interface.
+    #[cfg_attr(feature = "clap", arg(long, value_enum, default_value_t = Visibility::Pub))]
+    pub visibility: Visibility,
+
+    /// If true, records, variants and unions derive `PartialEq`, and also
+    /// `Eq` when they don't contain floats, so generated values can be
+    /// compared in tests.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub derive_eq: bool,
+
+    /// If true, each generated record, variant, union, enum and flags type
+    /// gets `ABI_SIZE` and `ABI_ALIGN` constants describing its layout in the
+    /// canonical ABI.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub emit_layout_consts: bool,
+
+    /// If true, generated types and functions whose Rust name differs from
+    /// their WIT name get a `#[doc(alias = "...")]` with the WIT name, so
+    /// rustdoc search finds them by either name.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub doc_aliases: bool,
+
+    /// The ABI string used for the generated `extern` blocks and exported
+    /// functions, one of `"C"`, `"C-unwind"` or `"system"`. Defaults to
+    /// `"C"`.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub extern_abi: Option<String>,
+
+    /// If true, each module with imports also gets a `pub mod raw` declaring
+    /// the underlying canonical ABI functions, so they can be called directly
+    /// with already-lowered arguments.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub expose_raw_imports: bool,
+
+    /// If true, a `result` without `ok` or `err` payloads is represented by
+    /// `wit_bindgen_guest_rust::Status` rather than `Result<(), ()>`.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub unit_result_as_status: bool,
+
+    /// If true, each import returning a `list` also gets an `_into` variant
+    /// which writes the list into a caller-provided `Vec` instead of
+    /// allocating a new one.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub list_into_buffer: bool,
+
+    /// If true, the `extern` blocks declaring imports are only compiled for
+    /// `wasm32`, and calling an import panics on other targets. This lets the
+    /// bindings be checked and documented on the host without any unresolved
+    /// import symbols.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub wasm_only_externs: bool,
+
+    /// If true, enums implement `FromStr` and `Display` using the WIT names of
+    /// their cases. Enums used as the error of a `result` only get `FromStr`,
+    /// since they already implement `Display` with their name and code.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub enum_str: bool,
+
+    /// Overrides of the `wasm_import_module` used for imported interfaces, as
+    /// pairs of the WIT name of the interface and the module name to import
+    /// from instead.
+    #[cfg_attr(feature = "clap", arg(long, value_parser = parse_import_module_alias))]
+    pub import_module_aliases: Vec<(String, String)>,
+
+    /// If true, all generated code is placed in a module which allows
+    /// `unsafe_code` and re-exported from there, so the bindings can be used
+    /// in crates which `#![deny(unsafe_code)]`.
+    ///
+    /// Note that `#![forbid(unsafe_code)]` can't be overridden by `allow`, so
+    /// such crates must use `deny` instead.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub unsafe_module: bool,
+
+    /// If true, variants whose payloads don't own any data also get a
+    /// `#[repr(C)]` companion type `{Name}C`, a tag and a union of payloads,
+    /// for passing them across a C boundary.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub c_tagged_variants: bool,
+
+    /// If true, generated type and trait names keep the casing of their WIT
+    /// names, with `-` replaced by `_`, rather than being camel-cased.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub preserve_wit_case: bool,
+
+    /// If set, payloads of variant cases whose canonical ABI size exceeds
+    /// this many bytes are stored in a `Box`, so a single large case doesn't
+    /// make every value of the variant as large.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub box_large_variants: Option<usize>,
+
+    /// If true, the documentation of each import notes the core wasm module
+    /// and function name it's imported from.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub doc_abi_details: bool,
+
+    /// If true, a `{world}.symbols.json` file is emitted alongside the
+    /// bindings listing the core wasm imports and exports along with the Rust
+    /// items they correspond to.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub emit_symbol_map: bool,
+
+    /// If true, each import is split into an `#[inline(always)]` wrapper
+    /// and an `#[inline(never)]` `{name}_raw` function doing the actual
+    /// lowering and lifting, so profilers attribute time to the latter and
+    /// it can be called directly.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub split_raw_wrappers: bool,
+
+    /// If true, lifted lists are checked to lie within linear memory in debug
+    /// builds, panicking instead of reading out of bounds if a host passes a
+    /// bogus pointer or length.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub debug_list_bounds: bool,
+
+    /// Attribute macros, such as `my_crate::reflect`, applied in order as
+    /// `#[...]` to every generated record, variant, union, enum and flags
+    /// type, before its derives.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub type_attribute_macros: Vec<String>,
+
+    /// The type of a context passed as `&mut` to every method of the export
+    /// traits, which is obtained from the trait's `with_context` method
+    /// implemented by the user.
+    ///
+    /// This is used in the generated modules, so it should be an absolute
+    /// path such as `crate::State`.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub export_context_type: Option<String>,
+
+    /// Alignments to use for records instead of their canonical ABI
+    /// alignment, as pairs of the WIT name of a record and its alignment, for
+    /// example to allow aligned SIMD loads from lists of the record.
+    ///
+    /// An alignment can only increase that of the canonical ABI, and must
+    /// divide the size of the record so lists of it keep the same stride.
+    #[cfg_attr(feature = "clap", arg(long, value_parser = parse_record_align_override))]
+    pub record_align_overrides: Vec<(String, usize)>,
+
+    /// If true, named `list` types are generated as newtype structs wrapping
+    /// a `Vec`, rather than as type aliases, so that methods can be added to
+    /// them.
+    ///
+    /// This applies to the owned form of the type; borrowed forms used in the
+    /// parameters of imports remain aliases of slices.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub newtype_list_aliases: bool,
+
+    /// A prefix prepended verbatim to the names of all generated types, for
+    /// example `Wasi` to turn `InputStream` into `WasiInputStream`, to avoid
+    /// collisions between the types of several worlds in one module.
+    ///
+    /// The names of functions, modules and export traits aren't prefixed.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub type_name_prefix: Option<String>,
+
+    /// If true, lifting an invalid discriminant calls a `#[cold]` function in
+    /// the runtime which panics, rather than panicking inline, to keep the
+    /// code lifting valid values compact.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub cold_trap_fns: bool,
+
+    /// A header, such as a license or a generated-code marker, emitted
+    /// verbatim at the top of the generated file.
+    ///
+    /// This must consist of Rust comments and may span multiple lines.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub generated_header: Option<String>,
+
+    /// If set, a `pub const ABI_VERSION: u32` is generated, which is a hash
+    /// of the WIT signatures of the world's functions and the types they use,
+    /// and is returned by a core wasm function exported with this name.
+    ///
+    /// A host can call the function after instantiation to detect that the
+    /// guest was built against different signatures.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub abi_version_const: Option<String>,
+
+    /// The string encoding to use in the canonical ABI.
+    ///
+    /// Bindings still use Rust strings; with `utf16` they are transcoded at
+    /// the boundary, which lets UTF-16-native hosts such as JS engines pass
+    /// strings without transcoding on their side.
+    #[cfg_attr(feature = "clap", arg(long, default_value_t = StringEncoding::default()))]
+    pub string_encoding: StringEncoding,
+
+    /// If true, imports take `list` parameters as `impl IntoIterator` of
+    /// their elements rather than as slices.
+    ///
+    /// The items are lowered directly into the canonical ABI buffer when the
+    /// iterator has an exact size hint, as every `ExactSizeIterator` does, and
+    /// are collected into a `Vec` first otherwise.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub list_params_impl_intoiterator: bool,
+
+    /// If true, variants get an `as_foo(&self) -> Option<&T>` and an
+    /// `into_foo(self) -> Result<T, Self>` accessor for each case `foo` with a
+    /// payload of type `T`, and `is_foo(&self) -> bool` for each case without
+    /// one.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub variant_accessors: bool,
+
+    /// If true, imports which return through a pointer share a module-level
+    /// `static mut` return area, as exports do, instead of each reserving
+    /// one on the stack.
+    ///
+    /// This reduces stack usage but assumes that the guest is
+    /// single-threaded and that imports aren't called reentrantly, since
+    /// each call overwrites the area.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub shared_import_return_area: bool,
+
+    /// If true, enums get an `as_wit_str(&self) -> &'static str` method, and a
+    /// `From` impl for `&'static str`, returning the WIT name of the case.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub enum_name_str: bool,
+
+    /// If true, the encoded component type is written to a separate
+    /// `{world}.component-type.bin` file which the bindings `include_bytes!`,
+    /// instead of being embedded as a byte array literal.
+    ///
+    /// This spares rustc from parsing a large literal for big worlds, but the
+    /// file must be placed next to the generated `.rs` file, so this isn't
+    /// available in the `generate!` macro.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub component_type_as_file: bool,
+
+    /// If true, a `WORLD_NAME` constant is generated holding the name of the
+    /// world, along with an `INTERFACE_NAME` constant in the module of each
+    /// imported and exported interface holding the name it has in the world.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub emit_name_consts: bool,
+
+    /// If set, lists passed to imports which need to be converted to the
+    /// canonical ABI are lowered into a buffer on the stack with room for
+    /// this many elements rather than into a heap allocation, trapping if a
+    /// list is longer.
+    ///
+    /// This is for guests which forbid heap allocation. It only applies to
+    /// the lists passed as parameters themselves: lists nested within them
+    /// are still lowered into heap allocations, and lists of types such as
+    /// integers are passed in place without any copy either way.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub inline_list_capacity: Option<usize>,
+
+    /// If true, the methods of export traits take parameters which own data,
+    /// such as strings, lists and records containing them, by reference
+    /// rather than by value.
+    ///
+    /// The lifted values are still allocated, but implementations which
+    /// only read them don't need to take ownership.
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub borrowed_export_params: bool,
+}
+
+/// Visibility of the modules generated for interfaces.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Visibility {
+    /// `pub mod`
+    #[default]
+    Pub,
+    /// `pub(crate) mod`
+    PubCrate,
+    /// `mod`
+    Private,
+}
+
+impl Visibility {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Visibility::Pub => "pub ",
+            Visibility::PubCrate => "pub(crate) ",
+            Visibility::Private => "",
+        }
+    }
 }
 
 impl Opts {
     pub fn build(self) -> Box<dyn WorldGenerator> {
         let mut r = RustWasm::new();
         r.skip = self.skip.iter().cloned().collect();
+        r.acronyms = self.acronyms.iter().map(|a| a.to_lowercase()).collect();
         r.opts = self;
         Box::new(r)
     }
+
+    fn extern_abi(&self) -> &str {
+        self.extern_abi.as_deref().unwrap_or("C")
+    }
+
+    /// Returns the `wasm_import_module` to use for the import named `name`.
+    fn import_module(&self, name: &str) -> String {
+        self.import_module_aliases
+            .iter()
+            .rev()
+            .find(|(from, _)| from == name)
+            .map_or(name, |(_, to)| to)
+            .to_string()
+    }
+
+    /// Returns the overridden alignment of the record named `name`, if any.
+    fn record_align(&self, name: &str) -> Option<usize> {
+        self.record_align_overrides
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, align)| *align)
+    }
+}
+
+#[cfg(feature = "clap")]
+fn parse_import_module_alias(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((from, to)) => Ok((from.to_string(), to.to_string())),
+        None => Err(format!("expected `FROM=TO`, found `{s}`")),
+    }
+}
+
+#[cfg(feature = "clap")]
+fn parse_record_align_override(s: &str) -> Result<(String, usize), String> {
+    match s.split_once('=') {
+        Some((name, align)) => match align.parse() {
+            Ok(align) => Ok((name.to_string(), align)),
+            Err(e) => Err(format!("invalid alignment `{align}`: {e}")),
+        },
+        None => Err(format!("expected `RECORD=ALIGN`, found `{s}`")),
+    }
+}
+
+/// Generates bindings for `world` into the directory named by the `OUT_DIR`
+/// environment variable, for use from build scripts.
+///
+/// The generated files are written to a `wit-bindgen` subdirectory, and the
+/// returned path is that of `OUT_DIR/bindings.rs` which includes all of them,
+/// so the bindings can be used with:
+///
+/// ```ignore
+/// include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+/// ```
+pub fn generate_to_out_dir(
+    resolve: &Resolve,
+    world: WorldId,
+    opts: Opts,
+) -> anyhow::Result<PathBuf> {
+    use anyhow::Context;
+
+    let out_dir = std::env::var_os("OUT_DIR")
+        .map(PathBuf::from)
+        .context("`OUT_DIR` is not set, this must be called from a build script")?;
+    let dir = out_dir.join("wit-bindgen");
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("failed to create `{}`", dir.display()))?;
+
+    let mut files = Files::default();
+    opts.build().generate(resolve, world, &mut files)?;
+
+    let mut bindings = String::new();
+    for (name, contents) in files.iter() {
+        let path = dir.join(name);
+        std::fs::write(&path, contents)
+            .with_context(|| format!("failed to write `{}`", path.display()))?;
+        if name.ends_with(".rs") {
+            uwriteln!(bindings, "include!(\"wit-bindgen/{name}\");");
+        }
+    }
+    let path = out_dir.join("bindings.rs");
+    std::fs::write(&path, bindings)
+        .with_context(|| format!("failed to write `{}`", path.display()))?;
+    Ok(path)
+}
+
+/// Checks that Rust bindings can be generated for `world` without actually
+/// generating them.
+///
+/// This is intended for build scripts and CI which want to fail fast with a
+/// readable error when a world uses constructs this generator doesn't
+/// support, rather than panicking halfway through code generation.
+pub fn validate(resolve: &Resolve, world: WorldId) -> anyhow::Result<()> {
+    let world = &resolve.worlds[world];
+    for (name, item) in world.imports.iter().chain(world.exports.iter()) {
+        match item {
+            WorldItem::Function(func) => validate_func(resolve, name, func)?,
+            WorldItem::Interface(id) => {
+                let iface = &resolve.interfaces[*id];
+                for (_, ty) in iface.types.iter() {
+                    validate_type(resolve, name, *ty)?;
+                }
+                for (_, func) in iface.functions.iter() {
+                    validate_func(resolve, name, func)?;
+                }
+            }
+            WorldItem::Type(ty) => validate_type(resolve, name, *ty)?,
+        }
+    }
+
+    for (name, item) in world.exports.iter() {
+        let funcs = match item {
+            WorldItem::Function(func) => vec![func],
+            WorldItem::Interface(id) => resolve.interfaces[*id].functions.values().collect(),
+            WorldItem::Type(_) => Vec::new(),
+        };
+        for func in funcs {
+            let sig = resolve.wasm_signature(AbiVariant::GuestExport, func);
+            if sig.results.len() > 1 {
+                anyhow::bail!(
+                    "exported function `{}` in `{name}` has {} core wasm results \
+                     but the Rust generator only supports at most one",
+                    func.name,
+                    sig.results.len(),
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_func(resolve: &Resolve, item: &str, func: &Function) -> anyhow::Result<()> {
+    for (_, ty) in func.params.iter() {
+        validate_ty(resolve, item, ty)?;
+    }
+    for ty in func.results.iter_types() {
+        validate_ty(resolve, item, ty)?;
+    }
+    Ok(())
+}
+
+fn validate_ty(resolve: &Resolve, item: &str, ty: &Type) -> anyhow::Result<()> {
+    match ty {
+        Type::Id(id) => validate_type(resolve, item, *id),
+        _ => Ok(()),
+    }
+}
+
+/// Computes a hash of the WIT signatures of the functions imported and
+/// exported by `world`, including the structure of the types they use, which
+/// changes whenever any of them do.
+fn abi_version(resolve: &Resolve, world: WorldId) -> u32 {
+    let world = &resolve.worlds[world];
+    let mut sigs = String::new();
+    for (dir, items) in [("import", &world.imports), ("export", &world.exports)] {
+        for (name, item) in items.iter() {
+            let funcs = match item {
+                WorldItem::Function(func) => vec![("$root", func)],
+                WorldItem::Interface(id) => resolve.interfaces[*id]
+                    .functions
+                    .values()
+                    .map(|func| (name.as_str(), func))
+                    .collect(),
+                WorldItem::Type(_) => continue,
+            };
+            for (module, func) in funcs {
+                uwrite!(sigs, "{dir} {module}#{}(", func.name);
+                for (_, ty) in func.params.iter() {
+                    write_abi_type(resolve, ty, &mut sigs);
+                    sigs.push_str(", ");
+                }
+                sigs.push_str(") -> (");
+                for ty in func.results.iter_types() {
+                    write_abi_type(resolve, ty, &mut sigs);
+                    sigs.push_str(", ");
+                }
+                sigs.push_str(")\n");
+            }
+        }
+    }
+    // 32-bit FNV-1a, which is simple and won't change between releases.
+    sigs.bytes().fold(0x811c_9dc5, |hash, b| {
+        (hash ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Writes the structure of `ty` for `abi_version`. Type names are left out
+/// since renaming a type doesn't change its ABI, but the names of fields and
+/// cases are included since they determine how values are interpreted.
+fn write_abi_type(resolve: &Resolve, ty: &Type, out: &mut String) {
+    let id = match ty {
+        Type::Bool => return out.push_str("bool"),
+        Type::U8 => return out.push_str("u8"),
+        Type::S8 => return out.push_str("s8"),
+        Type::U16 => return out.push_str("u16"),
+        Type::S16 => return out.push_str("s16"),
+        Type::U32 => return out.push_str("u32"),
+        Type::S32 => return out.push_str("s32"),
+        Type::U64 => return out.push_str("u64"),
+        Type::S64 => return out.push_str("s64"),
+        Type::Float32 => return out.push_str("float32"),
+        Type::Float64 => return out.push_str("float64"),
+        Type::Char => return out.push_str("char"),
+        Type::String => return out.push_str("string"),
+        Type::Id(id) => *id,
+    };
+    let write_opt = |ty: &Option<Type>, out: &mut String| match ty {
+        Some(ty) => write_abi_type(resolve, ty, out),
+        None => out.push('_'),
+    };
+    match &resolve.types[id].kind {
+        TypeDefKind::Type(ty) => write_abi_type(resolve, ty, out),
+        TypeDefKind::Record(record) => {
+            out.push_str("record{");
+            for field in record.fields.iter() {
+                uwrite!(out, "{}: ", field.name);
+                write_abi_type(resolve, &field.ty, out);
+                out.push_str(", ");
+            }
+            out.push('}');
+        }
+        TypeDefKind::Flags(flags) => {
+            out.push_str("flags{");
+            for flag in flags.flags.iter() {
+                uwrite!(out, "{}, ", flag.name);
+            }
+            out.push('}');
+        }
+        TypeDefKind::Tuple(tuple) => {
+            out.push_str("tuple<");
+            for ty in tuple.types.iter() {
+                write_abi_type(resolve, ty, out);
+                out.push_str(", ");
+            }
+            out.push('>');
+        }
+        TypeDefKind::Variant(variant) => {
+            out.push_str("variant{");
+            for case in variant.cases.iter() {
+                uwrite!(out, "{}(", case.name);
+                write_opt(&case.ty, out);
+                out.push_str("), ");
+            }
+            out.push('}');
+        }
+        TypeDefKind::Enum(enum_) => {
+            out.push_str("enum{");
+            for case in enum_.cases.iter() {
+                uwrite!(out, "{}, ", case.name);
+            }
+            out.push('}');
+        }
+        TypeDefKind::Union(union) => {
+            out.push_str("union{");
+            for case in union.cases.iter() {
+                write_abi_type(resolve, &case.ty, out);
+                out.push_str(", ");
+            }
+            out.push('}');
+        }
+        TypeDefKind::Option(ty) => {
+            out.push_str("option<");
+            write_abi_type(resolve, ty, out);
+            out.push('>');
+        }
+        TypeDefKind::Result(result) => {
+            out.push_str("result<");
+            write_opt(&result.ok, out);
+            out.push_str(", ");
+            write_opt(&result.err, out);
+            out.push('>');
+        }
+        TypeDefKind::List(ty) => {
+            out.push_str("list<");
+            write_abi_type(resolve, ty, out);
+            out.push('>');
+        }
+        TypeDefKind::Future(ty) => {
+            out.push_str("future<");
+            write_opt(ty, out);
+            out.push('>');
+        }
+        TypeDefKind::Stream(stream) => {
+            out.push_str("stream<");
+            write_opt(&stream.element, out);
+            out.push_str(", ");
+            write_opt(&stream.end, out);
+            out.push('>');
+        }
+        TypeDefKind::Unknown => unreachable!(),
+    }
+}
+
+/// Checks that `opts` uses UTF-8 or UTF-16 strings, the encodings lowering supports.
+fn check_string_encoding(opts: &Opts) -> anyhow::Result<()> {
+    match opts.string_encoding {
+        StringEncoding::UTF8 => Ok(()),
+        StringEncoding::UTF16 if opts.raw_strings => {
+            anyhow::bail!("`raw_strings` requires the `utf8` string encoding")
+        }
+        StringEncoding::UTF16 => Ok(()),
+        StringEncoding::CompactUTF16 => {
+            anyhow::bail!("the `compact-utf16` string encoding is not supported")
+        }
+    }
+}
+
+/// Checks that the `record_align_overrides` in `opts` name records and don't
+/// change the stride of lists of them.
+///
+/// Records with an overridden alignment also can't be nested in records or
+/// tuples since that would move them to offsets other than those in the
+/// canonical ABI.
+fn check_record_align_overrides(resolve: &Resolve, opts: &Opts) -> anyhow::Result<()> {
+    if opts.record_align_overrides.is_empty() {
+        return Ok(());
+    }
+    let mut sizes = SizeAlign::default();
+    sizes.fill(resolve);
+    for (name, align) in opts.record_align_overrides.iter() {
+        let mut found = false;
+        for (id, ty) in resolve.types.iter() {
+            if ty.name.as_deref() != Some(name) || !matches!(ty.kind, TypeDefKind::Record(_)) {
+                continue;
+            }
+            found = true;
+            let size = sizes.size(&Type::Id(id));
+            let abi_align = sizes.align(&Type::Id(id));
+            if !align.is_power_of_two() || *align < abi_align || size % align != 0 {
+                anyhow::bail!(
+                    "alignment {align} of record `{name}` must be a power of two \
+                     no less than its canonical ABI alignment of {abi_align} \
+                     which divides its size of {size}"
+                );
+            }
+        }
+        if !found {
+            anyhow::bail!("alignment overridden for `{name}`, which isn't a record");
+        }
+    }
+    for (_, ty) in resolve.types.iter() {
+        let nested = match &ty.kind {
+            TypeDefKind::Record(record) => record.fields.iter().map(|f| f.ty).collect(),
+            TypeDefKind::Tuple(tuple) => tuple.types.clone(),
+            _ => continue,
+        };
+        for nested in nested {
+            if let Type::Id(id) = nested {
+                let nested = &resolve.types[id];
+                if let (Some(name), TypeDefKind::Record(_)) = (&nested.name, &nested.kind) {
+                    if opts.record_align(name).is_some() {
+                        anyhow::bail!(
+                            "record `{name}` has an overridden alignment so it \
+                             can't be nested in another record or tuple"
+                        );
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_type(resolve: &Resolve, item: &str, id: TypeId) -> anyhow::Result<()> {
+    let ty = &resolve.types[id];
+    let name = ty.name.as_deref().unwrap_or("<anonymous>");
+    match &ty.kind {
+        TypeDefKind::Flags(flags) => {
+            if let FlagsRepr::U32(n) = flags.repr() {
+                if n > 4 {
+                    anyhow::bail!(
+                        "flags `{name}` in `{item}` has {} flags but the Rust \
+                         generator supports at most 128",
+                        flags.flags.len(),
+                    );
+                }
+            }
+        }
+        TypeDefKind::Future(_) | TypeDefKind::Stream(_) => {
+            anyhow::bail!(
+                "`{item}` uses a stream or future type, but stream/future types \
+                 are not yet supported by the Rust generator"
+            );
+        }
+        TypeDefKind::Record(record) => {
+            for field in record.fields.iter() {
+                validate_ty(resolve, item, &field.ty)?;
+            }
+        }
+        TypeDefKind::Tuple(tuple) => {
+            for ty in tuple.types.iter() {
+                validate_ty(resolve, item, ty)?;
+            }
+        }
+        TypeDefKind::Variant(variant) => {
+            for case in variant.cases.iter() {
+                if let Some(ty) = &case.ty {
+                    validate_ty(resolve, item, ty)?;
+                }
+            }
+        }
+        TypeDefKind::Union(union) => {
+            for case in union.cases.iter() {
+                validate_ty(resolve, item, &case.ty)?;
+            }
+        }
+        TypeDefKind::Result(result) => {
+            for ty in result.ok.iter().chain(result.err.iter()) {
+                validate_ty(resolve, item, ty)?;
+            }
+        }
+        TypeDefKind::Option(ty) | TypeDefKind::List(ty) | TypeDefKind::Type(ty) => {
+            validate_ty(resolve, item, ty)?;
+        }
+        TypeDefKind::Enum(_) | TypeDefKind::Unknown => {}
+    }
+    Ok(())
 }
 
 impl RustWasm {
@@ -107,13 +996,19 @@ impl RustWasm {
             default_param_mode,
             return_pointer_area_size: 0,
             return_pointer_area_align: 0,
+            raw_imports: Source::default(),
         }
     }
 }
 
 impl WorldGenerator for RustWasm {
-    fn preprocess(&mut self, resolve: &Resolve, _name: &str) {
+    fn preprocess(&mut self, resolve: &Resolve, world: WorldId) -> anyhow::Result<()> {
+        validate(resolve, world)?;
+        check_extern_abi(self.opts.extern_abi())?;
+        check_string_encoding(&self.opts)?;
+        check_record_align_overrides(resolve, &self.opts)?;
         self.types.analyze(resolve);
+        Ok(())
     }
 
     fn import_interface(
@@ -125,8 +1020,10 @@ impl WorldGenerator for RustWasm {
     ) {
         let prev = self.interface_names.insert(id, name.to_snake_case());
         assert!(prev.is_none());
-        let mut gen = self.interface(Some(name), resolve, TypeMode::AllBorrowed("'a"), true);
+        let module = self.opts.import_module(name);
+        let mut gen = self.interface(Some(&module), resolve, TypeMode::AllBorrowed("'a"), true);
         gen.current_interface = Some(id);
+        gen.print_interface_name_const(name);
         gen.types(id);
 
         for (_, func) in resolve.interfaces[id].functions.iter() {
@@ -143,7 +1040,8 @@ impl WorldGenerator for RustWasm {
         funcs: &[(&str, &Function)],
         _files: &mut Files,
     ) {
-        let mut gen = self.interface(Some("$root"), resolve, TypeMode::AllBorrowed("'a"), true);
+        let module = self.opts.import_module("$root");
+        let mut gen = self.interface(Some(&module), resolve, TypeMode::AllBorrowed("'a"), true);
 
         for (_, func) in funcs {
             gen.generate_guest_import(func);
@@ -163,6 +1061,7 @@ impl WorldGenerator for RustWasm {
         self.interface_names.insert(id, name.to_snake_case());
         let mut gen = self.interface(None, resolve, TypeMode::Owned, false);
         gen.current_interface = Some(id);
+        gen.print_interface_name_const(name);
         gen.types(id);
         gen.generate_exports(name, Some(name), resolve.interfaces[id].functions.values());
         gen.finish_append_submodule(name);
@@ -197,7 +1096,23 @@ impl WorldGenerator for RustWasm {
         self.src.push_str(&src);
     }
 
-    fn finish(&mut self, resolve: &Resolve, world: WorldId, files: &mut Files) {
+    fn finish(
+        &mut self,
+        resolve: &Resolve,
+        world: WorldId,
+        files: &mut Files,
+    ) -> anyhow::Result<()> {
+        if self.opts.wasm_bindgen_compat {
+            check_wasm_bindgen_compat(resolve, world)?;
+        }
+        let mut others = vec![(
+            "cabi_realloc".to_string(),
+            "the allocator of `wit_bindgen_guest_rust`".to_string(),
+        )];
+        if let Some(name) = &self.opts.abi_version_const {
+            others.push((name.clone(), "the `abi_version_const` function".to_string()));
+        }
+        self.symbols.check_export_collisions(others)?;
         let name = &resolve.worlds[world].name;
         if !self.exports.is_empty() {
             let macro_name = if let Some(name) = self.opts.export_macro_name.as_ref() {
@@ -210,13 +1125,21 @@ impl WorldGenerator for RustWasm {
             } else {
                 ""
             };
+            // The exports are expanded in the crate using the macro, so they
+            // need their own `allow` in addition to the module's.
+            let allow_unsafe = if self.opts.unsafe_module {
+                "#[allow(unsafe_code)]"
+            } else {
+                ""
+            };
             uwrite!(
                 self.src,
                 "
                     /// Declares the export of the component's world for the
                     /// given type.
                     {macro_export}
-                    macro_rules! {macro_name}(($t:ident) => {{
+                    macro_rules! {macro_name}(($t:ty) => {{
+                        {allow_unsafe}
                         const _: () = {{
                 "
             );
@@ -243,6 +1166,34 @@ impl WorldGenerator for RustWasm {
             );
         }
 
+        if self.opts.emit_name_consts {
+            uwriteln!(
+                self.src,
+                "/// The name of the world these bindings were generated for.
+                pub const WORLD_NAME: &str = \"{name}\";"
+            );
+        }
+
+        if let Some(export_name) = &self.opts.abi_version_const {
+            let abi = self.opts.extern_abi();
+            uwrite!(
+                self.src,
+                "
+                    /// A hash of the core wasm signatures of the functions
+                    /// imported and exported by this world.
+                    pub const ABI_VERSION: u32 = {version};
+
+                    #[doc(hidden)]
+                    #[cfg(target_arch = \"wasm32\")]
+                    #[export_name = \"{export_name}\"]
+                    extern \"{abi}\" fn __wit_bindgen_abi_version() -> u32 {{
+                        ABI_VERSION
+                    }}
+                ",
+                version = abi_version(resolve, world),
+            );
+        }
+
         self.src.push_str("\n#[cfg(target_arch = \"wasm32\")]\n");
 
         // The custom section name here must start with "component-type" but
@@ -253,14 +1204,18 @@ impl WorldGenerator for RustWasm {
             .push_str(&format!("#[link_section = \"component-type:{}\"]\n", name,));
 
         let component_type =
-            wit_component::metadata::encode(resolve, world, wit_component::StringEncoding::UTF8)
-                .unwrap();
+            wit_component::metadata::encode(resolve, world, self.opts.string_encoding)?;
         self.src.push_str(&format!(
             "pub static __WIT_BINDGEN_COMPONENT_TYPE: [u8; {}] = ",
             component_type.len()
         ));
-        self.src.push_str(&format!("{:?};\n", component_type));
-
+        if self.opts.component_type_as_file {
+            self.src.push_str(&format!(
+                "*include_bytes!(\"{name}.component-type.bin\");\n"
+            ));
+        } else {
+            self.src.push_str(&format!("{:?};\n", component_type));
+        }
         self.src.push_str(
             "
             #[inline(never)]
@@ -270,6 +1225,48 @@ impl WorldGenerator for RustWasm {
         ",
         );
 
+        if !self.prelude.is_empty() {
+            self.src.push_str(
+                "
+                /// Re-exports of the traits to implement for this world's
+                /// exports along with the types used in their signatures.
+                pub mod prelude {
+                ",
+            );
+            for (_, path) in self.prelude.iter() {
+                uwriteln!(self.src, "pub use super::{path};");
+            }
+            self.src.push_str("}\n");
+        }
+
+        if self.opts.unsafe_module {
+            let module = format!("__{}_bindings", name.to_snake_case());
+            let body = String::from(mem::take(&mut self.src));
+            uwrite!(
+                self.src,
+                "
+                    // Contains unsafe code, which is allowed for this module
+                    // alone so that the crate including it may deny it.
+                    #[allow(unsafe_code)]
+                    #[macro_use]
+                    #[doc(hidden)]
+                    mod {module} {{
+                        {body}
+                    }}
+                    pub use {module}::*;
+                "
+            );
+        }
+
+        if let Some(header) = &self.opts.generated_header {
+            // This bypasses `Source::push_str` which would re-indent it.
+            let src = self.src.as_mut_string();
+            if !header.ends_with('\n') {
+                src.insert(0, '\n');
+            }
+            src.insert_str(0, header);
+        }
+
         let mut src = mem::take(&mut self.src);
         if self.opts.rustfmt {
             let mut child = Command::new("rustfmt")
@@ -296,6 +1293,28 @@ impl WorldGenerator for RustWasm {
         }
 
         files.push(&format!("{name}.rs"), src.as_bytes());
+        if self.opts.emit_symbol_map {
+            files.push(
+                &format!("{name}.symbols.json"),
+                self.symbols.to_json().as_bytes(),
+            );
+        }
+        if self.opts.component_type_as_file {
+            files.push(&format!("{name}.component-type.bin"), &component_type);
+        }
+        Ok(())
+    }
+}
+
+impl RustWasm {
+    /// Records `path` to be re-exported from the `prelude` module as `name`.
+    ///
+    /// Only the first item with a given name is re-exported so the `prelude`
+    /// module never contains conflicting imports.
+    fn add_to_prelude(&mut self, name: String, path: String) {
+        if self.prelude.iter().all(|(n, _)| *n != name) {
+            self.prelude.push((name, path));
+        }
     }
 }
 
@@ -310,16 +1329,51 @@ struct InterfaceGenerator<'a> {
     default_param_mode: TypeMode,
     return_pointer_area_size: usize,
     return_pointer_area_align: usize,
+    raw_imports: Source,
 }
 
 impl InterfaceGenerator<'_> {
+    /// Prints the `INTERFACE_NAME` constant for `emit_name_consts`, where
+    /// `name` is the name of the interface in the world.
+    fn print_interface_name_const(&mut self, name: &str) {
+        if self.gen.opts.emit_name_consts {
+            uwriteln!(
+                self.src,
+                "/// The name of this interface in the world.
+                pub const INTERFACE_NAME: &str = \"{name}\";"
+            );
+        }
+    }
+
+    /// Returns the names of the type and static of the module's return area.
+    ///
+    /// Imports and exports of bare functions are both generated at the root
+    /// of the world, so their shared return areas need different names.
+    fn ret_area_names(&self) -> (&'static str, &'static str) {
+        if self.in_import {
+            ("_ImportRetArea", "_IMPORT_RET_AREA")
+        } else {
+            ("_RetArea", "_RET_AREA")
+        }
+    }
+
     fn generate_exports<'a>(
         &mut self,
         name: &str,
         interface_name: Option<&str>,
         funcs: impl Iterator<Item = &'a Function> + Clone,
     ) {
-        let camel = name.to_upper_camel_case();
+        // Interfaces which only define types, or whose functions are all
+        // skipped, have nothing to implement, so neither an empty trait nor
+        // a check in the export macro that it's implemented is generated.
+        if funcs.clone().all(|func| self.gen.skip.contains(&func.name)) {
+            return;
+        }
+        let camel = self.trait_name(name);
+        self.print_doc_alias_name(&camel, name);
+        if self.gen.opts.preserve_wit_case {
+            self.src.push_str("#[allow(non_camel_case_types)]\n");
+        }
         uwriteln!(self.src, "pub trait {camel} {{");
         for func in funcs.clone() {
             if self.gen.skip.contains(&func.name) {
@@ -327,72 +1381,595 @@ impl InterfaceGenerator<'_> {
             }
             let mut sig = FnSig::default();
             sig.private = true;
+            sig.borrowed_params = self.gen.opts.borrowed_export_params;
+            if let Some(ctx) = &self.gen.opts.export_context_type {
+                sig.self_arg = Some(format!("ctx: &mut {ctx}"));
+            }
+            let nparams = func.params.len() + usize::from(sig.self_arg.is_some());
+            self.src.push_str(allow_too_many_arguments(nparams));
             self.print_signature(func, TypeMode::Owned, &sig);
             self.src.push_str(";\n");
         }
+        if let Some(ctx) = &self.gen.opts.export_context_type {
+            uwriteln!(
+                self.src,
+                "
+                    /// Calls `f` with the context passed to the methods of
+                    /// this trait when they're called as exports.
+                    fn with_context<R>(f: impl FnOnce(&mut {ctx}) -> R) -> R;
+                "
+            );
+        }
         uwriteln!(self.src, "}}");
 
+        if self.gen.opts.prelude {
+            let path = match interface_name {
+                Some(_) => format!("{}::{camel}", name.to_snake_case()),
+                None => camel.clone(),
+            };
+            self.gen.add_to_prelude(camel, path);
+            for func in funcs.clone() {
+                if self.gen.skip.contains(&func.name) {
+                    continue;
+                }
+                for (_, ty) in func.params.iter() {
+                    self.add_type_to_prelude(ty);
+                }
+                for ty in func.results.iter_types() {
+                    self.add_type_to_prelude(ty);
+                }
+            }
+        }
+
+        // Check up front that the type given to the export macro implements
+        // the trait, so that a missing impl is reported against the trait
+        // itself rather than at whichever generated export happens to use it
+        // first.
+        let snake = name.to_snake_case();
+        let prefix = self.gen.opts.macro_call_prefix.as_deref().unwrap_or("");
+        let path = match interface_name {
+            Some(_) => format!("{prefix}{snake}::{camel}"),
+            None => format!("{prefix}{camel}"),
+        };
+        let mut macro_src = Source::default();
+        uwrite!(
+            macro_src,
+            "
+                #[doc(hidden)]
+                fn __assert_impl_{snake}<T: {path}>() {{}}
+                const _: fn() = __assert_impl_{snake}::<$t>;
+            "
+        );
+        self.gen.exports.push(macro_src);
+
         for func in funcs {
             self.generate_guest_export(name, func, interface_name);
         }
     }
 
+    /// Adds the named types referenced by `ty` to the `prelude` module,
+    /// looking through anonymous types such as `list<T>` and `option<T>`.
+    fn add_type_to_prelude(&mut self, ty: &Type) {
+        let id = match ty {
+            Type::Id(id) => *id,
+            _ => return,
+        };
+        let resolve = self.resolve;
+        let ty = &resolve.types[id];
+        if ty.name.is_some() {
+            let name = self.result_name(id);
+            let path = match ty.owner {
+                TypeOwner::Interface(iface) => match self.gen.interface_names.get(&iface) {
+                    Some(module) => format!("{module}::{name}"),
+                    None => return,
+                },
+                TypeOwner::World(_) => name.clone(),
+                TypeOwner::None => return,
+            };
+            self.gen.add_to_prelude(name, path);
+            return;
+        }
+        match &ty.kind {
+            TypeDefKind::Tuple(tuple) => {
+                for ty in tuple.types.iter() {
+                    self.add_type_to_prelude(ty);
+                }
+            }
+            TypeDefKind::Result(result) => {
+                for ty in result.ok.iter().chain(result.err.iter()) {
+                    self.add_type_to_prelude(ty);
+                }
+            }
+            TypeDefKind::Option(ty) | TypeDefKind::List(ty) | TypeDefKind::Type(ty) => {
+                self.add_type_to_prelude(ty);
+            }
+            _ => {}
+        }
+    }
+
     fn finish(&mut self) -> String {
+        // Without `std` there's no prelude providing `String` and `Vec` to the
+        // type definitions, so interface modules import them from `alloc`.
+        // This isn't done at the root of the world where the bindings share
+        // the scope of the caller of the macro.
+        let import_alloc = self.gen.opts.no_std && self.current_interface.is_some();
+        let mut import_rt = self.return_pointer_area_align > 0 || import_alloc;
+        if import_rt && self.current_interface.is_none() {
+            import_rt = !mem::replace(&mut self.gen.root_imports_rt, true);
+        }
+        if import_rt {
+            self.src.push_str(
+                "
+                    #[allow(unused_imports)]
+                    use wit_bindgen_guest_rust::rt::{alloc, vec::Vec, string::String};
+                ",
+            );
+        }
         if self.return_pointer_area_align > 0 {
+            let (ty, name) = self.ret_area_names();
             uwrite!(
                 self.src,
                 "
-                    #[allow(unused_imports)]
-                    use wit_bindgen_guest_rust::rt::{{alloc, vec::Vec, string::String}};
-
                     #[repr(align({align}))]
-                    struct _RetArea([u8; {size}]);
-                    static mut _RET_AREA: _RetArea = _RetArea([0; {size}]);
+                    struct {ty}([u8; {size}]);
+                    static mut {name}: {ty} = {ty}([0; {size}]);
                 ",
                 align = self.return_pointer_area_align,
                 size = self.return_pointer_area_size,
             );
         }
 
+        if !self.raw_imports.is_empty() {
+            let module = self.wasm_import_module.unwrap();
+            let abi = self.gen.opts.extern_abi().to_string();
+            let raw = String::from(mem::take(&mut self.raw_imports));
+            let cfg = if self.gen.opts.wasm_only_externs {
+                "#[cfg(target_arch = \"wasm32\")]"
+            } else {
+                ""
+            };
+            uwrite!(
+                self.src,
+                "
+                    /// The canonical ABI functions imported from `{module}`.
+                    ///
+                    /// These take and return core wasm values exactly as the
+                    /// component model lowers them, bypassing all lifting and
+                    /// lowering done by the wrappers in the parent module.
+                    ///
+                    /// # Safety
+                    ///
+                    /// Calling these is only sound if the caller upholds the
+                    /// canonical ABI itself:
+                    ///
+                    /// * Pointer arguments must point to memory laid out as the
+                    ///   canonical ABI requires for the lowered type and must
+                    ///   stay valid for the duration of the call.
+                    /// * Strings must be valid UTF-8.
+                    /// * Functions with a return pointer parameter write their
+                    ///   results to it, so it must point to a writable area
+                    ///   that is large and aligned enough for the results.
+                    /// * Lists and strings returned by the host are allocated
+                    ///   with `cabi_realloc` and are owned by the caller, who
+                    ///   must free them with the matching size and alignment.
+                    /// * Enum, variant and flags values returned by the host
+                    ///   are not validated.
+                    {cfg}
+                    pub mod raw {{
+                        #[link(wasm_import_module = \"{module}\")]
+                        extern \"{abi}\" {{
+                            {raw}
+                        }}
+                    }}
+                ",
+            );
+        }
+
         mem::take(&mut self.src).into()
     }
 
+    /// Prints `FromStr` and `Display` impls for the enum `id` which use the
+    /// WIT names of its cases.
+    fn print_enum_str(&mut self, id: TypeId, name: &str, enum_: &Enum) {
+        let name = self.type_name(name);
+        uwriteln!(
+            self.src,
+            "impl core::str::FromStr for {name} {{
+                type Err = wit_bindgen_guest_rust::ParseEnumError;
+
+                fn from_str(s: &str) -> Result<{name}, Self::Err> {{
+                    match s {{"
+        );
+        for case in enum_.cases.iter() {
+            let case_name = case.name.to_upper_camel_case();
+            uwriteln!(self.src, "\"{}\" => Ok({name}::{case_name}),", case.name);
+        }
+        uwriteln!(
+            self.src,
+            "_ => Err(wit_bindgen_guest_rust::ParseEnumError),
+                    }}
+                }}
+            }}"
+        );
+
+        // Error enums already display their name along with their code.
+        if self.info(id).error {
+            return;
+        }
+        uwriteln!(
+            self.src,
+            "impl core::fmt::Display for {name} {{
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{
+                    f.write_str(match self {{"
+        );
+        for case in enum_.cases.iter() {
+            let case_name = case.name.to_upper_camel_case();
+            uwriteln!(self.src, "{name}::{case_name} => \"{}\",", case.name);
+        }
+        uwriteln!(
+            self.src,
+            "}})
+                }}
+            }}"
+        );
+    }
+
+    /// Prints `as_wit_str` for the enum `name`, along with a conversion to
+    /// `&'static str` which uses it.
+    fn print_enum_name_str(&mut self, name: &str, enum_: &Enum) {
+        let name = self.type_name(name);
+        uwriteln!(
+            self.src,
+            "impl {name} {{
+                /// Returns the name of this case in WIT.
+                pub fn as_wit_str(&self) -> &'static str {{
+                    match self {{"
+        );
+        for case in enum_.cases.iter() {
+            let case_name = case.name.to_upper_camel_case();
+            uwriteln!(self.src, "{name}::{case_name} => \"{}\",", case.name);
+        }
+        uwriteln!(
+            self.src,
+            "}}
+                }}
+            }}
+
+            impl From<{name}> for &'static str {{
+                fn from(e: {name}) -> &'static str {{
+                    e.as_wit_str()
+                }}
+            }}"
+        );
+    }
+
+    /// Prints the `#[repr(C)]` tagged union `{name}C` mirroring the variant
+    /// `name`, along with a conversion from the variant and accessors for
+    /// each case.
+    fn print_c_tagged_variant(&mut self, id: TypeId, name: &str, variant: &Variant) {
+        let name = self.type_name(name);
+        uwriteln!(
+            self.src,
+            "/// A C-compatible representation of [`{name}`]: `tag` is the index
+            /// of the case and `payload` holds that case's payload, if any.
+            #[repr(C)]
+            #[derive(Clone, Copy)]
+            pub struct {name}C {{
+                pub tag: i32,
+                pub payload: {name}CPayload,
+            }}
+
+            /// The payloads of [`{name}C`].
+            #[repr(C)]
+            #[derive(Clone, Copy)]
+            pub union {name}CPayload {{
+                /// Used by cases without a payload.
+                pub _none: (),"
+        );
+        for case in variant.cases.iter() {
+            if let Some(ty) = &case.ty {
+                uwrite!(self.src, "pub {}: ", to_rust_ident(&case.name));
+                self.print_ty(ty, TypeMode::Owned);
+                self.src.push_str(",\n");
+            }
+        }
+        self.src.push_str("}\n");
+
+        uwriteln!(
+            self.src,
+            "impl From<{name}> for {name}C {{
+                fn from(v: {name}) -> {name}C {{
+                    match v {{"
+        );
+        for (i, case) in variant.cases.iter().enumerate() {
+            let case_name = case.name.to_upper_camel_case();
+            match &case.ty {
+                Some(ty) => {
+                    let field = to_rust_ident(&case.name);
+                    let e = if self.box_payload(id, ty) { "*e" } else { "e" };
+                    uwriteln!(
+                        self.src,
+                        "{name}::{case_name}(e) => {name}C {{
+                            tag: {i},
+                            payload: {name}CPayload {{ {field}: {e} }},
+                        }},"
+                    );
+                }
+                None => uwriteln!(
+                    self.src,
+                    "{name}::{case_name} => {name}C {{
+                        tag: {i},
+                        payload: {name}CPayload {{ _none: () }},
+                    }},"
+                ),
+            }
+        }
+        self.src.push_str("}\n}\n}\n");
+
+        uwriteln!(self.src, "impl {name}C {{");
+        for (i, case) in variant.cases.iter().enumerate() {
+            let field = to_rust_ident(&case.name);
+            match &case.ty {
+                Some(ty) => {
+                    uwrite!(
+                        self.src,
+                        "/// Returns the payload of the `{}` case, or `None` if
+                        /// this is a different case.
+                        pub fn {field}(&self) -> Option<",
+                        case.name,
+                    );
+                    self.print_ty(ty, TypeMode::Owned);
+                    uwriteln!(
+                        self.src,
+                        "> {{
+                            if self.tag == {i} {{
+                                Some(unsafe {{ self.payload.{field} }})
+                            }} else {{
+                                None
+                            }}
+                        }}"
+                    );
+                }
+                None => uwriteln!(
+                    self.src,
+                    "/// Returns whether this is the `{}` case.
+                    pub fn is_{}(&self) -> bool {{
+                        self.tag == {i}
+                    }}",
+                    case.name,
+                    case.name.to_snake_case(),
+                ),
+            }
+        }
+        self.src.push_str("}\n");
+    }
+
+    /// Prints `ABI_SIZE` and `ABI_ALIGN` constants for each Rust type
+    /// generated for `id`, if `emit_layout_consts` is enabled.
+    fn print_layout_consts(&mut self, id: TypeId) {
+        if !self.gen.opts.emit_layout_consts {
+            return;
+        }
+        let size = self.sizes.size(&Type::Id(id));
+        let align = self.sizes.align(&Type::Id(id));
+        let info = self.info(id);
+        // Enums and flags are always generated under their own name, while
+        // other types get a name per mode they're used in.
+        let ty = &self.resolve.types[id];
+        let modes = match &ty.kind {
+            TypeDefKind::Enum(_) | TypeDefKind::Flags(_) => {
+                vec![(self.type_name(ty.name.as_ref().unwrap()), TypeMode::Owned)]
+            }
+            _ => self.modes_of(id),
+        };
+        for (name, mode) in modes {
+            let lt = self.lifetime_for(&info, mode);
+            self.push_str("impl");
+            self.print_generics(lt);
+            uwrite!(self.src, " {name}");
+            self.print_generics(lt);
+            uwriteln!(
+                self.src,
+                " {{
+                    /// The size, in bytes, of this type in the canonical ABI.
+                    pub const ABI_SIZE: usize = {size};
+                    /// The alignment, in bytes, of this type in the canonical ABI.
+                    pub const ABI_ALIGN: usize = {align};
+                }}"
+            );
+        }
+    }
+
     fn finish_append_submodule(mut self, name: &str) {
         let module = self.finish();
         let snake = name.to_snake_case();
+        let vis = self.gen.opts.visibility.as_str();
         uwriteln!(
             self.gen.src,
             "
                 #[allow(clippy::all)]
-                pub mod {snake} {{
+                {vis}mod {snake} {{
                     {module}
                 }}
             "
         );
     }
 
+    /// Adds the declaration of the core wasm function backing `func` to the
+    /// `raw` module emitted in `finish`.
+    fn print_raw_import(&mut self, func: &Function) {
+        let module = self.wasm_import_module.unwrap();
+        let sig = self.resolve.wasm_signature(AbiVariant::GuestImport, func);
+        let name = &func.name;
+        uwrite!(
+            self.raw_imports,
+            "
+                #[cfg_attr(target_arch = \"wasm32\", link_name = \"{name}\")]
+                #[cfg_attr(not(target_arch = \"wasm32\"), link_name = \"{module}_{name}\")]
+                pub fn {}(\
+            ",
+            to_rust_ident(name),
+        );
+        for (i, param) in sig.params.iter().enumerate() {
+            uwrite!(self.raw_imports, "arg{i}: {}, ", wasm_type(*param));
+        }
+        self.raw_imports.push_str(")");
+        for result in sig.results.iter() {
+            uwrite!(self.raw_imports, " -> {}", wasm_type(*result));
+        }
+        self.raw_imports.push_str(";\n");
+    }
+
     fn generate_guest_import(&mut self, func: &Function) {
         if self.gen.skip.contains(&func.name) {
             return;
         }
 
-        let sig = FnSig::default();
-        let param_mode = TypeMode::AllBorrowed("'_");
         match &func.kind {
             FunctionKind::Freestanding => {}
         }
+        let path = match self.current_interface {
+            Some(id) => format!(
+                "{}::{}",
+                self.gen.interface_names[&id],
+                to_rust_ident(&func.name)
+            ),
+            None => to_rust_ident(&func.name),
+        };
+        self.gen.symbols.imports.push(ImportSymbol {
+            module: self.wasm_import_module.unwrap().to_string(),
+            name: func.name.clone(),
+            path,
+        });
+
+        let documented;
+        let func = if self.gen.opts.doc_abi_details {
+            documented = self.with_abi_details(func);
+            &documented
+        } else {
+            func
+        };
+        self.print_guest_import(func, None);
+
+        if self.gen.opts.list_into_buffer {
+            if let Some(element) = self.list_result(func) {
+                self.print_guest_import(func, Some(&element));
+            }
+        }
+
+        if self.gen.opts.expose_raw_imports {
+            self.print_raw_import(func);
+        }
+    }
+
+    /// Converts the WIT name of an interface or world into the name of its
+    /// Rust trait, which is also the name of a type before any prefix.
+    fn trait_name(&self, name: &str) -> String {
+        if self.gen.opts.preserve_wit_case {
+            let ident = name.replace('-', "_");
+            // `to_rust_ident` only differs from snake-casing for keywords,
+            // which are escaped the same way here.
+            return if to_rust_ident(&ident) != ident.to_snake_case() {
+                format!("{ident}_")
+            } else {
+                ident
+            };
+        }
+        if self.gen.acronyms.is_empty() {
+            return name.to_upper_camel_case();
+        }
+        name.split('-')
+            .map(|word| {
+                if self.gen.acronyms.contains(&word.to_lowercase()) {
+                    word.to_uppercase()
+                } else {
+                    word.to_upper_camel_case()
+                }
+            })
+            .collect()
+    }
+
+    /// Returns a copy of the import `func` whose docs end with a note of the
+    /// core wasm function it calls.
+    fn with_abi_details(&self, func: &Function) -> Function {
+        let module = self.wasm_import_module.unwrap();
+        let mut func = func.clone();
+        let docs = func.docs.contents.get_or_insert_with(String::new);
+        if !docs.trim().is_empty() {
+            docs.push_str("\n\n");
+        }
+        uwrite!(
+            docs,
+            "Calls the core wasm function `{}` imported from the `{module}` module.",
+            func.name,
+        );
+        func
+    }
+
+    /// Returns the element type of the result of `func` if it returns a
+    /// single `list`.
+    fn list_result(&self, func: &Function) -> Option<Type> {
+        let mut types = func.results.iter_types();
+        match (types.next(), types.next()) {
+            (Some(Type::Id(id)), None) => match &self.resolve.types[*id].kind {
+                TypeDefKind::List(element) => Some(*element),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Prints the wrapper for the import `func`, or with `list_into` its
+    /// `_into` variant writing the returned list of `list_into` elements
+    /// into a caller-provided buffer.
+    fn print_guest_import(&mut self, func: &Function, list_into: Option<&Type>) {
+        let raw;
+        let sig_func = if self.gen.opts.split_raw_wrappers {
+            raw = self.print_raw_wrapper(func, list_into);
+            &raw
+        } else {
+            func
+        };
+        if self.gen.opts.native_import_trap {
+            self.src.push_str("#[cfg(target_arch = \"wasm32\")]\n");
+        }
         self.src.push_str("#[allow(clippy::all)]\n");
-        let params = self.print_signature(func, param_mode, &sig);
+        if self.gen.opts.split_raw_wrappers {
+            self.src.push_str("#[inline(never)]\n");
+        }
+        let params = self.print_import_signature(sig_func, list_into);
         self.src.push_str("{\n");
-        self.src.push_str(
-            "
-                #[allow(unused_imports)]
-                use wit_bindgen_guest_rust::rt::{{alloc, vec::Vec, string::String}};
-            ",
-        );
+        match &self.gen.opts.allocator_path {
+            Some(path) => uwrite!(
+                self.src,
+                "
+                    #[allow(unused_imports)]
+                    use wit_bindgen_guest_rust::rt::{{vec::Vec, string::String}};
+                    #[allow(unused_imports)]
+                    use {path} as alloc;
+                ",
+            ),
+            None => self.src.push_str(
+                "
+                    #[allow(unused_imports)]
+                    use wit_bindgen_guest_rust::rt::{{alloc, vec::Vec, string::String}};
+                ",
+            ),
+        }
         self.src.push_str("unsafe {\n");
 
+        let mut iter_params = Vec::new();
+        if self.gen.opts.list_params_impl_intoiterator {
+            for (name, ty) in func.params.iter() {
+                if self.list_element(ty).is_some() {
+                    iter_params.push(to_rust_ident(name));
+                }
+            }
+        }
+
         let mut f = FunctionBindgen::new(self, params);
+        f.list_into = list_into.is_some();
+        f.iter_params = iter_params;
         f.gen.resolve.call(
             AbiVariant::GuestImport,
             LiftLower::LowerArgsLiftResults,
@@ -420,14 +1997,106 @@ impl InterfaceGenerator<'_> {
                 ",
             );
         }
-        self.src.push_str(&String::from(src));
-
-        self.src.push_str("}\n");
-        self.src.push_str("}\n");
+        self.src.push_str(&String::from(src));
+
+        self.src.push_str("}\n");
+        self.src.push_str("}\n");
+
+        if self.gen.opts.native_import_trap {
+            // On other targets there's nothing to link the import against, so
+            // provide a body which panics with the name of the import instead.
+            let module = self.wasm_import_module.unwrap();
+            self.src.push_str("#[cfg(not(target_arch = \"wasm32\"))]\n");
+            self.src
+                .push_str("#[allow(clippy::all, unused_variables)]\n");
+            self.print_import_signature(sig_func, list_into);
+            uwriteln!(
+                self.src,
+                "{{
+                    panic!(\"import `{module}::{}` is not available on this target\")
+                }}",
+                func.name,
+            );
+        }
+    }
+
+    /// Prints the wrapper for the import `func` which forwards to its `_raw`
+    /// version, returning the function whose signature is that of the `_raw`
+    /// version.
+    fn print_raw_wrapper(&mut self, func: &Function, list_into: Option<&Type>) -> Function {
+        self.src.push_str("#[allow(clippy::all)]\n");
+        self.src.push_str("#[inline(always)]\n");
+        let mut params = self.print_import_signature(func, list_into);
+        let mut raw = func.clone();
+        raw.name = format!("{}-raw", func.name);
+        raw.docs = Docs {
+            contents: Some(format!(
+                "The lowering and lifting of [`{}`], which forwards to this.",
+                to_rust_ident(&func.name),
+            )),
+        };
+        let mut raw_name = to_rust_ident(&raw.name);
+        if list_into.is_some() {
+            raw_name.push_str("_into");
+            params.push("out".to_string());
+        }
+        uwriteln!(self.src, "{{ {raw_name}({}) }}", params.join(", "));
+        raw
+    }
+
+    fn print_import_signature(&mut self, func: &Function, list_into: Option<&Type>) -> Vec<String> {
+        let nparams = func.params.len() + usize::from(list_into.is_some());
+        self.src.push_str(allow_too_many_arguments(nparams));
+        let (param_mode, sig) = self.import_sig(func);
+        let element = match list_into {
+            Some(element) => element,
+            None => return self.print_signature(func, param_mode, &sig),
+        };
+        let name = to_rust_ident(&func.name);
+        uwriteln!(
+            self.src,
+            "/// Like [`{name}`], but clears `out` and writes the returned list
+            /// into it, reusing its capacity instead of allocating a new `Vec`."
+        );
+        let generics = sig.generics.as_deref().unwrap_or("");
+        uwrite!(self.src, "pub fn {name}_into{generics}(");
+        let mut params = Vec::new();
+        for (name, param) in func.params.iter() {
+            let name = to_rust_ident(name);
+            uwrite!(self.src, "{name}: ");
+            self.print_param_ty(param, param_mode, &sig);
+            self.src.push_str(",");
+            params.push(name);
+        }
+        self.src.push_str("out: &mut Vec<");
+        self.print_ty(element, TypeMode::Owned);
+        self.src.push_str(">)");
+        params
+    }
 
-        match &func.kind {
-            FunctionKind::Freestanding => {}
+    /// Returns the mode of the parameters of the import `func` along with its
+    /// signature, which needs a named lifetime when list parameters are
+    /// iterators of borrowed items.
+    fn import_sig(&self, func: &Function) -> (TypeMode, FnSig) {
+        let mut sig = FnSig::default();
+        if !self.gen.opts.list_params_impl_intoiterator {
+            return (TypeMode::AllBorrowed("'_"), sig);
         }
+        sig.iter_list_params = true;
+        let borrows = func
+            .params
+            .iter()
+            .filter_map(|(_, ty)| self.list_element(ty))
+            .any(|element| match element {
+                Type::String => true,
+                Type::Id(id) => self.info(id).has_list,
+                _ => false,
+            });
+        if !borrows {
+            return (TypeMode::AllBorrowed("'_"), sig);
+        }
+        sig.generics = Some("<'a>".to_string());
+        (TypeMode::AllBorrowed("'a"), sig)
     }
 
     fn generate_guest_export(
@@ -440,10 +2109,32 @@ impl InterfaceGenerator<'_> {
             return;
         }
 
+        let trait_bound = self.trait_name(module_name);
         let module_name = module_name.to_snake_case();
-        let trait_bound = module_name.to_upper_camel_case();
         let name_snake = func.name.to_snake_case();
         let export_name = func.core_export_name(interface_name);
+        let export_cfg = if self.gen.opts.native_export_stubs {
+            "#[cfg(target_arch = \"wasm32\")]"
+        } else {
+            ""
+        };
+        let abi = self.gen.opts.extern_abi().to_string();
+        let sig = self.resolve.wasm_signature(AbiVariant::GuestExport, func);
+        let allow = allow_too_many_arguments(sig.params.len());
+        self.gen.symbols.exports.push(ExportSymbol {
+            name: export_name.to_string(),
+            post_return: self
+                .resolve
+                .guest_export_needs_post_return(func)
+                .then(|| format!("cabi_post_{export_name}")),
+            path: match interface_name {
+                Some(_) => format!(
+                    "{module_name}::{trait_bound}::{}",
+                    to_rust_ident(&func.name)
+                ),
+                None => format!("{trait_bound}::{}", to_rust_ident(&func.name)),
+            },
+        });
         let mut macro_src = Source::default();
         // Generate, simultaneously, the actual lifting/lowering function within
         // the original module (`call_{name_snake}`) as well as the function
@@ -457,6 +2148,7 @@ impl InterfaceGenerator<'_> {
             self.src,
             "
                 #[doc(hidden)]
+                {allow}
                 pub unsafe fn call_{name_snake}<T: {trait_bound}>(\
             ",
         );
@@ -464,13 +2156,14 @@ impl InterfaceGenerator<'_> {
             macro_src,
             "
                 #[doc(hidden)]
+                {export_cfg}
                 #[export_name = \"{export_name}\"]
                 #[allow(non_snake_case)]
-                unsafe extern \"C\" fn __export_{module_name}_{name_snake}(\
+                {allow}
+                unsafe extern \"{abi}\" fn __export_{module_name}_{name_snake}(\
             ",
         );
 
-        let sig = self.resolve.wasm_signature(AbiVariant::GuestExport, func);
         let mut params = Vec::new();
         for (i, param) in sig.params.iter().enumerate() {
             let name = format!("arg{}", i);
@@ -487,7 +2180,8 @@ impl InterfaceGenerator<'_> {
                 uwrite!(self.src, " -> {}", wasm_type(sig.results[0]));
                 uwrite!(macro_src, " -> {}", wasm_type(sig.results[0]));
             }
-            _ => unimplemented!(),
+            // Rejected with a descriptive error by `validate` in `preprocess`.
+            n => unreachable!("export `{}` has {n} core wasm results", func.name),
         }
 
         self.push_str(" {\n");
@@ -539,9 +2233,10 @@ impl InterfaceGenerator<'_> {
                 macro_src,
                 "
                     #[doc(hidden)]
+                    {export_cfg}
                     #[export_name = \"cabi_post_{export_name}\"]
                     #[allow(non_snake_case)]
-                    unsafe extern \"C\" fn __post_return_{module_name}_{name_snake}(\
+                    unsafe extern \"{abi}\" fn __post_return_{module_name}_{name_snake}(\
                 "
             );
             let mut params = Vec::new();
@@ -573,8 +2268,152 @@ impl InterfaceGenerator<'_> {
             self.src.push_str("}\n");
         }
 
+        if self.gen.opts.wasm_bindgen_compat {
+            self.generate_wasm_bindgen_wrapper(
+                &mut macro_src,
+                &prefix,
+                &trait_bound,
+                func,
+                interface_name,
+            );
+        }
+
         self.gen.exports.push(macro_src);
     }
+
+    /// Generates a `#[wasm_bindgen]` function in the export macro which
+    /// forwards to the export trait, for use by a `wasm-bindgen` web build.
+    ///
+    /// Signatures are checked by `check_wasm_bindgen_compat` before the
+    /// bindings are emitted, so only scalars and strings show up here.
+    fn generate_wasm_bindgen_wrapper(
+        &self,
+        macro_src: &mut Source,
+        prefix: &str,
+        trait_bound: &str,
+        func: &Function,
+        interface_name: Option<&str>,
+    ) {
+        let js_name = match interface_name {
+            Some(iface) => format!("{iface}-{}", func.name).to_lower_camel_case(),
+            None => func.name.to_lower_camel_case(),
+        };
+        let wrapper_name = js_name.to_snake_case();
+        uwrite!(
+            macro_src,
+            "
+                #[wasm_bindgen::prelude::wasm_bindgen(js_name = \"{js_name}\")]
+                pub fn __wasm_bindgen_{wrapper_name}(\
+            "
+        );
+        let mut args = Vec::new();
+        for (name, ty) in func.params.iter() {
+            let name = to_rust_ident(name);
+            if let Some(ty) = wasm_bindgen_type(ty, self.gen.opts.raw_strings) {
+                uwrite!(macro_src, "{name}: {ty},");
+            }
+            if self.gen.opts.borrowed_export_params && self.is_borrowed_param(ty) {
+                args.push(format!("&{name}"));
+            } else {
+                args.push(name);
+            }
+        }
+        macro_src.push_str(")");
+        if let Some(ty) = func.results.iter_types().next() {
+            if let Some(ty) = wasm_bindgen_type(ty, self.gen.opts.raw_strings) {
+                uwrite!(macro_src, " -> {ty}");
+            }
+        }
+        let func_name = to_rust_ident(&func.name);
+        let call = if self.gen.opts.export_context_type.is_some() {
+            args.insert(0, "ctx".to_string());
+            format!(
+                "<$t as {prefix}{trait_bound}>::with_context(|ctx| \
+                 <$t as {prefix}{trait_bound}>::{func_name}({}))",
+                args.join(", "),
+            )
+        } else {
+            format!(
+                "<$t as {prefix}{trait_bound}>::{func_name}({})",
+                args.join(", "),
+            )
+        };
+        uwriteln!(macro_src, " {{ {call} }}");
+    }
+}
+
+/// Returns the Rust type used for `ty` in a `#[wasm_bindgen]` wrapper, or
+/// `None` if `wasm-bindgen` can't pass it across the JS boundary.
+fn wasm_bindgen_type(ty: &Type, raw_strings: bool) -> Option<&'static str> {
+    Some(match ty {
+        Type::Bool => "bool",
+        Type::U8 => "u8",
+        Type::U16 => "u16",
+        Type::U32 => "u32",
+        Type::U64 => "u64",
+        Type::S8 => "i8",
+        Type::S16 => "i16",
+        Type::S32 => "i32",
+        Type::S64 => "i64",
+        Type::Float32 => "f32",
+        Type::Float64 => "f64",
+        Type::Char => "char",
+        Type::String if raw_strings => "Vec<u8>",
+        Type::String => "String",
+        Type::Id(_) => return None,
+    })
+}
+
+/// Returns `#[allow(clippy::too_many_arguments)]` for a function with more
+/// than the lint's default threshold of seven parameters.
+fn allow_too_many_arguments(params: usize) -> &'static str {
+    if params > 7 {
+        "#[allow(clippy::too_many_arguments)]\n"
+    } else {
+        ""
+    }
+}
+
+/// Checks that `abi` is an ABI string that stable Rust accepts for wasm32
+/// `extern "..."` blocks.
+fn check_extern_abi(abi: &str) -> anyhow::Result<()> {
+    const ABIS: &[&str] = &["C", "C-unwind", "system"];
+    if !ABIS.contains(&abi) {
+        anyhow::bail!(
+            "unknown extern ABI `{abi}`, expected one of: {}",
+            ABIS.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Checks that every function exported by `world` can be wrapped by
+/// `wasm_bindgen_compat`.
+fn check_wasm_bindgen_compat(resolve: &Resolve, world: WorldId) -> anyhow::Result<()> {
+    for (name, item) in resolve.worlds[world].exports.iter() {
+        let funcs = match item {
+            WorldItem::Function(func) => vec![func],
+            WorldItem::Interface(id) => resolve.interfaces[*id].functions.values().collect(),
+            WorldItem::Type(_) => Vec::new(),
+        };
+        for func in funcs {
+            let unsupported = func
+                .params
+                .iter()
+                .map(|(_, ty)| ty)
+                .chain(func.results.iter_types())
+                .any(|ty| wasm_bindgen_type(ty, false).is_none());
+            if unsupported || func.results.len() > 1 {
+                anyhow::bail!(
+                    "exported function `{}` in `{name}` cannot be wrapped for \
+                     `wasm_bindgen_compat`, which only supports scalar and \
+                     string parameters and at most one scalar or string result",
+                    func.name,
+                );
+            }
+        }
+    }
+    Ok(())
 }
 
 impl<'a> RustGenerator<'a> for InterfaceGenerator<'a> {
@@ -600,10 +2439,80 @@ impl<'a> RustGenerator<'a> for InterfaceGenerator<'a> {
         !self.gen.opts.no_std
     }
 
+    fn std_feature(&self) -> bool {
+        self.gen.opts.std_feature
+    }
+
     fn use_raw_strings(&self) -> bool {
         self.gen.opts.raw_strings
     }
 
+    fn impl_into_iterator(&self) -> bool {
+        self.gen.opts.impl_into_iterator
+    }
+
+    fn c_error_codes(&self) -> bool {
+        self.gen.opts.c_error_codes
+    }
+
+    fn derive_eq(&self) -> bool {
+        self.gen.opts.derive_eq
+    }
+
+    fn doc_aliases(&self) -> bool {
+        self.gen.opts.doc_aliases
+    }
+
+    fn unit_result_type(&self) -> Option<&'static str> {
+        if self.gen.opts.unit_result_as_status {
+            Some("wit_bindgen_guest_rust::Status")
+        } else {
+            None
+        }
+    }
+
+    fn preserve_wit_case(&self) -> bool {
+        self.gen.opts.preserve_wit_case
+    }
+
+    fn type_attribute_macros(&self) -> &[String] {
+        &self.gen.opts.type_attribute_macros
+    }
+
+    fn newtype_list_aliases(&self) -> bool {
+        self.gen.opts.newtype_list_aliases
+    }
+
+    fn variant_accessors(&self) -> bool {
+        self.gen.opts.variant_accessors
+    }
+
+    fn record_align(&self, id: TypeId) -> Option<usize> {
+        let ty = &self.resolve.types[id];
+        match (&ty.name, &ty.kind) {
+            (Some(name), TypeDefKind::Record(_)) => self.gen.opts.record_align(name),
+            _ => None,
+        }
+    }
+
+    fn box_payload(&self, id: TypeId, ty: &Type) -> bool {
+        match self.gen.opts.box_large_variants {
+            Some(max) => {
+                matches!(self.resolve.types[id].kind, TypeDefKind::Variant(_))
+                    && self.sizes.size(ty) > max
+            }
+            None => false,
+        }
+    }
+
+    fn type_name(&self, name: &str) -> String {
+        let name = self.trait_name(name);
+        match &self.gen.opts.type_name_prefix {
+            Some(prefix) => format!("{prefix}{name}"),
+            None => name,
+        }
+    }
+
     fn default_param_mode(&self) -> TypeMode {
         self.default_param_mode
     }
@@ -645,21 +2554,23 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
 
     fn type_record(&mut self, id: TypeId, _name: &str, record: &Record, docs: &Docs) {
         self.print_typedef_record(id, record, docs, false);
+        self.print_layout_consts(id);
     }
 
     fn type_tuple(&mut self, id: TypeId, _name: &str, tuple: &Tuple, docs: &Docs) {
         self.print_typedef_tuple(id, tuple, docs);
     }
 
-    fn type_flags(&mut self, _id: TypeId, name: &str, flags: &Flags, docs: &Docs) {
+    fn type_flags(&mut self, id: TypeId, name: &str, flags: &Flags, docs: &Docs) {
         self.src
             .push_str("wit_bindgen_guest_rust::bitflags::bitflags! {\n");
         self.rustdoc(docs);
+        let camel = self.type_name(name);
+        self.print_type_attrs(id, &camel);
+        self.print_type_attribute_macros();
         let repr = RustFlagsRepr::new(flags);
-        self.src.push_str(&format!(
-            "pub struct {}: {repr} {{\n",
-            name.to_upper_camel_case(),
-        ));
+        self.src
+            .push_str(&format!("pub struct {camel}: {repr} {{\n"));
         for (i, flag) in flags.flags.iter().enumerate() {
             self.rustdoc(&flag.docs);
             self.src.push_str(&format!(
@@ -673,7 +2584,7 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
 
         // Add a `from_bits_preserve` method.
         self.src
-            .push_str(&format!("impl {} {{\n", name.to_upper_camel_case()));
+            .push_str(&format!("impl {} {{\n", self.type_name(name)));
         self.src.push_str(&format!(
             "    /// Convert from a raw integer, preserving any unknown bits. See\n"
         ));
@@ -686,14 +2597,28 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.src.push_str(&format!("        Self {{ bits }}\n"));
         self.src.push_str(&format!("    }}\n"));
         self.src.push_str(&format!("}}\n"));
+        self.print_layout_consts(id);
     }
 
-    fn type_variant(&mut self, id: TypeId, _name: &str, variant: &Variant, docs: &Docs) {
+    fn type_variant(&mut self, id: TypeId, name: &str, variant: &Variant, docs: &Docs) {
         self.print_typedef_variant(id, variant, docs, false);
+        self.print_layout_consts(id);
+        // Union fields must be `Copy`, which boxed payloads nested in a
+        // payload aren't.
+        if self.gen.opts.c_tagged_variants
+            && !self.info(id).owns_data()
+            && variant
+                .cases
+                .iter()
+                .all(|c| c.ty.as_ref().map_or(true, |t| !self.has_boxed_payload(t)))
+        {
+            self.print_c_tagged_variant(id, name, variant);
+        }
     }
 
     fn type_union(&mut self, id: TypeId, _name: &str, union: &Union, docs: &Docs) {
         self.print_typedef_union(id, union, docs, false);
+        self.print_layout_consts(id);
     }
 
     fn type_option(&mut self, id: TypeId, _name: &str, payload: &Type, docs: &Docs) {
@@ -706,6 +2631,13 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
 
     fn type_enum(&mut self, id: TypeId, name: &str, enum_: &Enum, docs: &Docs) {
         self.print_typedef_enum(id, name, enum_, docs, &[], Box::new(|_| String::new()));
+        self.print_layout_consts(id);
+        if self.gen.opts.enum_str {
+            self.print_enum_str(id, name, enum_);
+        }
+        if self.gen.opts.enum_name_str {
+            self.print_enum_name_str(name, enum_);
+        }
     }
 
     fn type_alias(&mut self, id: TypeId, _name: &str, ty: &Type, docs: &Docs) {
@@ -716,10 +2648,11 @@ impl<'a> wit_bindgen_core::InterfaceGenerator<'a> for InterfaceGenerator<'a> {
         self.print_type_list(id, ty, docs);
     }
 
-    fn type_builtin(&mut self, _id: TypeId, name: &str, ty: &Type, docs: &Docs) {
+    fn type_builtin(&mut self, id: TypeId, name: &str, ty: &Type, docs: &Docs) {
         self.rustdoc(docs);
-        self.src
-            .push_str(&format!("pub type {}", name.to_upper_camel_case()));
+        let name = self.type_name(name);
+        self.print_type_attrs(id, &name);
+        self.src.push_str(&format!("pub type {name}"));
         self.src.push_str(" = ");
         self.print_ty(ty, TypeMode::Owned);
         self.src.push_str(";\n");
@@ -737,6 +2670,12 @@ struct FunctionBindgen<'a, 'b> {
     cleanup: Vec<(String, String)>,
     import_return_pointer_area_size: usize,
     import_return_pointer_area_align: usize,
+    /// Whether the returned list is written into the `out` parameter rather
+    /// than returned, for `list_into_buffer`.
+    list_into: bool,
+    /// The parameters which are `impl IntoIterator` rather than slices, for
+    /// `list_params_impl_intoiterator`.
+    iter_params: Vec<String>,
 }
 
 impl<'a, 'b> FunctionBindgen<'a, 'b> {
@@ -752,9 +2691,116 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
             cleanup: Vec::new(),
             import_return_pointer_area_size: 0,
             import_return_pointer_area_align: 0,
+            list_into: false,
+            iter_params: Vec::new(),
         }
     }
 
+    /// Returns whether `operand` is a list parameter given as an iterator,
+    /// which is lowered from a `SizedIter` over its items.
+    fn is_iter_param(&self, operand: &str) -> bool {
+        // Operands within blocks are elements of other values, which may
+        // happen to share the name of a parameter.
+        self.block_storage.is_empty() && self.iter_params.iter().any(|p| p == operand)
+    }
+
+    /// Emits the allocation of `layout`, which may be of zero size, binding
+    /// the resulting `*mut u8` to `ptr`.
+    fn alloc_list(&mut self, ptr: &str, layout: &str) {
+        uwriteln!(
+            self.src,
+            "let {ptr} = if {layout}.size() != 0 {{
+                let ptr = alloc::alloc({layout});
+                if ptr.is_null() {{
+                    alloc::handle_alloc_error({layout});
+                }}
+                ptr
+            }} else {{
+                core::ptr::null_mut()
+            }};"
+        );
+    }
+
+    /// Returns whether a list lifted here is the returned list which should be
+    /// written into `out`. Lists nested in other types are lifted in blocks.
+    fn lifts_into_out(&self) -> bool {
+        self.list_into && self.block_storage.is_empty()
+    }
+
+    /// Returns the arguments passed to the export trait's method for `func`,
+    /// borrowing those which own data with `borrowed_export_params`.
+    fn export_call_args(&self, func: &Function, operands: &[String]) -> String {
+        let borrowed = self.gen.gen.opts.borrowed_export_params;
+        func.params
+            .iter()
+            .zip(operands)
+            .map(|((_, ty), op)| {
+                if borrowed && self.gen.is_borrowed_param(ty) {
+                    format!("&{op}")
+                } else {
+                    op.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Returns the expression panicking when an invalid discriminant of a
+    /// `kind` is lifted.
+    fn invalid_discriminant(&self, kind: &str) -> String {
+        if self.gen.gen.opts.cold_trap_fns {
+            format!("wit_bindgen_guest_rust::rt::invalid_discriminant(\"{kind}\")")
+        } else {
+            format!("panic!(\"invalid {kind} discriminant\")")
+        }
+    }
+
+    /// Wraps the lifted `Vec` `vec` in the newtype generated for the list
+    /// type `ty` with `newtype_list_aliases`, if it's named.
+    fn wrap_list_newtype(&self, ty: TypeId, vec: String) -> String {
+        if self.gen.gen.opts.newtype_list_aliases && self.gen.resolve.types[ty].name.is_some() {
+            format!("{}({vec})", self.typename_lift(ty))
+        } else {
+            vec
+        }
+    }
+
+    /// Converts an owned list being lowered into a `Vec`, as it may be a
+    /// newtype generated with `newtype_list_aliases`.
+    fn unwrap_list_newtype(&self, operand: &str) -> String {
+        if self.gen.gen.opts.newtype_list_aliases {
+            format!("Vec::from({operand})")
+        } else {
+            operand.to_string()
+        }
+    }
+
+    /// Returns the record `ty` refers to if its alignment is overridden.
+    ///
+    /// Lists of such records may not be aligned for the Rust type, so they're
+    /// copied into a new allocation rather than being used in place.
+    fn overaligned_record(&self, ty: &Type) -> Option<TypeId> {
+        let mut ty = *ty;
+        while let Type::Id(id) = ty {
+            match &self.gen.resolve.types[id].kind {
+                TypeDefKind::Type(t) => ty = *t,
+                TypeDefKind::Record(_) => return self.gen.record_align(id).map(|_| id),
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// Emits a copy of the `len` elements of `size` bytes at `ptr` into the
+    /// `Vec` named `vec`, which has room for them.
+    fn copy_list_into(&mut self, ptr: &str, len: &str, size: usize, vec: &str) {
+        uwriteln!(
+            self.src,
+            "core::ptr::copy_nonoverlapping({ptr} as *const u8, {vec}.as_mut_ptr() as *mut u8, {len} * {size});
+            {vec}.set_len({len});"
+        );
+    }
+
     fn emit_cleanup(&mut self) {
         for (ptr, layout) in mem::take(&mut self.cleanup) {
             self.push_str(&format!(
@@ -772,6 +2818,30 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
         }
     }
 
+    /// Emits a check that the lifted list length `len` doesn't exceed
+    /// `max_list_len`, if configured, before anything is allocated for it.
+    fn check_list_len(&mut self, len: &str) {
+        if let Some(max) = self.gen.gen.opts.max_list_len {
+            uwriteln!(
+                self.src,
+                "if {len} as usize > {max} {{
+                    panic!(\"list length {{}} exceeds the maximum of {max}\", {len});
+                }}"
+            );
+        }
+    }
+
+    /// Emits a check that the `len` elements of `size` bytes at `ptr` are
+    /// within linear memory, if `debug_list_bounds` is set.
+    fn check_list_bounds(&mut self, ptr: &str, len: &str, size: usize) {
+        if self.gen.gen.opts.debug_list_bounds {
+            uwriteln!(
+                self.src,
+                "wit_bindgen_guest_rust::rt::check_list_bounds({ptr}, {len} as usize, {size});"
+            );
+        }
+    }
+
     fn declare_import(
         &mut self,
         module_name: &str,
@@ -779,29 +2849,49 @@ impl<'a, 'b> FunctionBindgen<'a, 'b> {
         params: &[WasmType],
         results: &[WasmType],
     ) -> String {
+        let mut sig = String::new();
+        for param in params.iter() {
+            sig.push_str("_: ");
+            sig.push_str(wasm_type(*param));
+            sig.push_str(", ");
+        }
+        sig.push_str(")");
+        assert!(results.len() < 2);
+        for result in results.iter() {
+            sig.push_str(" -> ");
+            sig.push_str(wasm_type(*result));
+        }
+
         // Define the actual function we're calling inline
+        let wasm_only = self.gen.gen.opts.wasm_only_externs;
+        if wasm_only {
+            self.push_str("#[cfg(target_arch = \"wasm32\")]\n");
+        }
+        let abi = self.gen.gen.opts.extern_abi().to_string();
         uwriteln!(
             self.src,
             "
                 #[link(wasm_import_module = \"{module_name}\")]
-                extern \"C\" {{
+                extern \"{abi}\" {{
                     #[cfg_attr(target_arch = \"wasm32\", link_name = \"{name}\")]
                     #[cfg_attr(not(target_arch = \"wasm32\"), link_name = \"{module_name}_{name}\")]
-                    fn wit_import(\
+                    fn wit_import({sig};
+                }}
             "
         );
-        for param in params.iter() {
-            self.push_str("_: ");
-            self.push_str(wasm_type(*param));
-            self.push_str(", ");
-        }
-        self.push_str(")");
-        assert!(results.len() < 2);
-        for result in results.iter() {
-            self.push_str(" -> ");
-            self.push_str(wasm_type(*result));
+        if wasm_only {
+            // Without the extern block there's no symbol to link against, so
+            // stand in a function which panics with the name of the import.
+            uwriteln!(
+                self.src,
+                "
+                    #[cfg(not(target_arch = \"wasm32\"))]
+                    unsafe fn wit_import({sig} {{
+                        panic!(\"import `{module_name}::{name}` is not available on this target\")
+                    }}
+                "
+            );
         }
-        self.push_str(";\n}\n");
         "wit_import".to_string()
     }
 }
@@ -875,8 +2965,9 @@ impl Bindgen for FunctionBindgen<'_, '_> {
         // Imports get a per-function return area to facilitate using the
         // stack whereas exports use a per-module return area to cut down on
         // stack usage. Note that for imports this also facilitates "adapter
-        // modules" for components to not have data segments.
-        if self.gen.in_import {
+        // modules" for components to not have data segments, unless
+        // `shared_import_return_area` asks for the per-module area instead.
+        if self.gen.in_import && !self.gen.gen.opts.shared_import_return_area {
             self.import_return_pointer_area_size = self.import_return_pointer_area_size.max(size);
             self.import_return_pointer_area_align =
                 self.import_return_pointer_area_align.max(align);
@@ -884,7 +2975,8 @@ impl Bindgen for FunctionBindgen<'_, '_> {
         } else {
             self.gen.return_pointer_area_size = self.gen.return_pointer_area_size.max(size);
             self.gen.return_pointer_area_align = self.gen.return_pointer_area_align.max(align);
-            uwriteln!(self.src, "let ptr{tmp} = _RET_AREA.0.as_mut_ptr() as i32;");
+            let (_, name) = self.gen.ret_area_names();
+            uwriteln!(self.src, "let ptr{tmp} = {name}.0.as_mut_ptr() as i32;");
         }
         format!("ptr{}", tmp)
     }
@@ -905,6 +2997,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
         results: &mut Vec<String>,
     ) {
         let unchecked = self.gen.gen.opts.unchecked;
+        let safe_enum_lift = self.gen.gen.opts.safe_enum_lift;
         let mut top_as = |cvt: &str| {
             let mut s = operands.pop().unwrap();
             s.push_str(" as ");
@@ -983,19 +3076,19 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 results.push(format!("match {} {{ true => 1, false => 0 }}", operands[0]));
             }
             Instruction::BoolFromI32 => {
+                // A comparison is as cheap as a transmute and, unlike one,
+                // is sound for any value the host might pass.
                 if unchecked {
-                    results.push(format!(
-                        "core::mem::transmute::<u8, bool>({} as u8)",
-                        operands[0],
-                    ));
+                    results.push(format!("{} != 0", operands[0]));
                 } else {
                     results.push(format!(
                         "match {} {{
                             0 => false,
                             1 => true,
-                            _ => panic!(\"invalid bool discriminant\"),
+                            _ => {},
                         }}",
                         operands[0],
+                        self.invalid_discriminant("bool"),
                     ));
                 }
             }
@@ -1009,7 +3102,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
             }
             Instruction::FlagsLift { name, flags, .. } => {
                 let repr = RustFlagsRepr::new(flags);
-                let name = name.to_upper_camel_case();
+                let name = self.gen.type_name(name);
                 let mut result = format!("{}::empty()", name);
                 for (i, op) in operands.iter().enumerate() {
                     result.push_str(&format!(
@@ -1055,7 +3148,20 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 for (case, block) in variant.cases.iter().zip(blocks) {
                     let case_name = case.name.to_upper_camel_case();
                     self.push_str(&format!("{name}::{case_name}"));
-                    if case.ty.is_some() {
+                    let boxed = case
+                        .ty
+                        .as_ref()
+                        .map_or(false, |t| self.gen.box_payload(*ty, t));
+                    if boxed {
+                        // Exports lower owned results, which can be moved out
+                        // of the box, while imports only borrow their params.
+                        let unbox = if self.gen.in_import {
+                            "wit_bindgen_guest_rust::rt::unbox(&e)"
+                        } else {
+                            "*e"
+                        };
+                        self.push_str(&format!("(e) => {{\nlet e = {unbox};\n{block}\n}},\n"));
+                    } else if case.ty.is_some() {
                         self.push_str(&format!("(e) => {block},\n"));
                     } else {
                         self.push_str(&format!(" => {{\n{block}\n}}\n"));
@@ -1065,13 +3171,14 @@ impl Bindgen for FunctionBindgen<'_, '_> {
             }
 
             // In unchecked mode when this type is a named enum then we know we
-            // defined the type so we can transmute directly into it.
+            // defined the type so we can transmute directly into it, unless
+            // `safe_enum_lift` asks for the discriminant to always be matched.
             Instruction::VariantLift { name, variant, .. }
-                if variant.cases.iter().all(|c| c.ty.is_none()) && unchecked =>
+                if variant.cases.iter().all(|c| c.ty.is_none()) && unchecked && !safe_enum_lift =>
             {
                 self.blocks.drain(self.blocks.len() - variant.cases.len()..);
                 let mut result = format!("core::mem::transmute::<_, ");
-                result.push_str(&name.to_upper_camel_case());
+                result.push_str(&self.gen.type_name(name));
                 result.push_str(">(");
                 result.push_str(&operands[0]);
                 result.push_str(" as ");
@@ -1094,16 +3201,19 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                     } else {
                         i.to_string()
                     };
-                    let block = if case.ty.is_some() {
-                        format!("({block})")
-                    } else {
-                        String::new()
+                    let block = match &case.ty {
+                        Some(t) if self.gen.box_payload(*ty, t) => {
+                            format!("(wit_bindgen_guest_rust::rt::boxed::Box::new({block}))")
+                        }
+                        Some(_) => format!("({block})"),
+                        None => String::new(),
                     };
                     let case = case.name.to_upper_camel_case();
                     result.push_str(&format!("{pat} => {name}::{case}{block},\n"));
                 }
                 if !unchecked {
-                    result.push_str("_ => panic!(\"invalid enum discriminant\"),\n");
+                    let invalid = self.invalid_discriminant("enum");
+                    result.push_str(&format!("_ => {invalid},\n"));
                 }
                 result.push_str("}");
                 results.push(result);
@@ -1152,7 +3262,8 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                     result.push_str(&format!("{pat} => {name}::{case_name}({block}),\n"));
                 }
                 if !unchecked {
-                    result.push_str("_ => panic!(\"invalid union discriminant\"),\n");
+                    let invalid = self.invalid_discriminant("union");
+                    result.push_str(&format!("_ => {invalid},\n"));
                 }
                 result.push_str("}");
                 results.push(result);
@@ -1180,9 +3291,9 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 assert_eq!(none, "()");
                 let operand = &operands[0];
                 let invalid = if unchecked {
-                    "core::hint::unreachable_unchecked()"
+                    "core::hint::unreachable_unchecked()".to_string()
                 } else {
-                    "panic!(\"invalid enum discriminant\")"
+                    self.invalid_discriminant("enum")
                 };
                 results.push(format!(
                     "match {operand} {{
@@ -1202,37 +3313,58 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 let ok = self.blocks.pop().unwrap();
                 self.let_results(result_types.len(), results);
                 let operand = &operands[0];
-                let ok_binding = if result.ok.is_some() { "e" } else { "_" };
-                let err_binding = if result.err.is_some() { "e" } else { "_" };
-                self.push_str(&format!(
-                    "match {operand} {{
-                        Ok({ok_binding}) => {{ {ok} }},
-                        Err({err_binding}) => {{ {err} }},
-                    }};"
-                ));
+                let status = self.gen.unit_result_type();
+                match status.filter(|_| result.ok.is_none() && result.err.is_none()) {
+                    Some(status) => self.push_str(&format!(
+                        "match {operand} {{
+                            {status}::Ok => {{ {ok} }},
+                            {status}::Err => {{ {err} }},
+                        }};"
+                    )),
+                    None => {
+                        let ok_binding = if result.ok.is_some() { "e" } else { "_" };
+                        let err_binding = if result.err.is_some() { "e" } else { "_" };
+                        self.push_str(&format!(
+                            "match {operand} {{
+                                Ok({ok_binding}) => {{ {ok} }},
+                                Err({err_binding}) => {{ {err} }},
+                            }};"
+                        ));
+                    }
+                }
             }
 
-            Instruction::ResultLift { .. } => {
+            Instruction::ResultLift { result, .. } => {
                 let err = self.blocks.pop().unwrap();
                 let ok = self.blocks.pop().unwrap();
                 let operand = &operands[0];
                 let invalid = if unchecked {
-                    "core::hint::unreachable_unchecked()"
+                    "core::hint::unreachable_unchecked()".to_string()
                 } else {
-                    "panic!(\"invalid enum discriminant\")"
+                    self.invalid_discriminant("enum")
                 };
-                results.push(format!(
-                    "match {operand} {{
-                        0 => Ok({ok}),
-                        1 => Err({err}),
-                        _ => {invalid},
-                    }}"
-                ));
+                let status = self.gen.unit_result_type();
+                match status.filter(|_| result.ok.is_none() && result.err.is_none()) {
+                    Some(status) => results.push(format!(
+                        "match {operand} {{
+                            0 => {{ {ok}; {status}::Ok }},
+                            1 => {{ {err}; {status}::Err }},
+                            _ => {invalid},
+                        }}"
+                    )),
+                    None => results.push(format!(
+                        "match {operand} {{
+                            0 => Ok({ok}),
+                            1 => Err({err}),
+                            _ => {invalid},
+                        }}"
+                    )),
+                }
             }
 
             Instruction::EnumLower { enum_, name, .. } => {
                 let mut result = format!("match {} {{\n", operands[0]);
-                let name = name.to_upper_camel_case();
+                let name = self.gen.type_name(name);
                 for (i, case) in enum_.cases.iter().enumerate() {
                     let case = case.name.to_upper_camel_case();
                     result.push_str(&format!("{name}::{case} => {i},\n"));
@@ -1242,10 +3374,11 @@ impl Bindgen for FunctionBindgen<'_, '_> {
             }
 
             // In unchecked mode when this type is a named enum then we know we
-            // defined the type so we can transmute directly into it.
-            Instruction::EnumLift { enum_, name, .. } if unchecked => {
+            // defined the type so we can transmute directly into it, unless
+            // `safe_enum_lift` asks for the discriminant to always be matched.
+            Instruction::EnumLift { enum_, name, .. } if unchecked && !safe_enum_lift => {
                 let mut result = format!("core::mem::transmute::<_, ");
-                result.push_str(&name.to_upper_camel_case());
+                result.push_str(&self.gen.type_name(name));
                 result.push_str(">(");
                 result.push_str(&operands[0]);
                 result.push_str(" as ");
@@ -1258,16 +3391,77 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 let mut result = format!("match ");
                 result.push_str(&operands[0]);
                 result.push_str(" {\n");
-                let name = name.to_upper_camel_case();
+                let name = self.gen.type_name(name);
                 for (i, case) in enum_.cases.iter().enumerate() {
                     let case = case.name.to_upper_camel_case();
                     result.push_str(&format!("{i} => {name}::{case},\n"));
                 }
-                result.push_str("_ => panic!(\"invalid enum discriminant\"),\n");
+                let invalid = self.invalid_discriminant("enum");
+                result.push_str(&format!("_ => {invalid},\n"));
                 result.push_str("}");
                 results.push(result);
             }
 
+            Instruction::ListCanonLower { element, realloc }
+                if self.is_iter_param(&operands[0]) =>
+            {
+                // The items of an iterator are lowered by writing each one in
+                // place, as a slice would have been laid out.
+                assert!(realloc.is_none());
+                let tmp = self.tmp();
+                let iter = format!("iter{tmp}");
+                let base = format!("base{tmp}");
+                let layout = format!("layout{tmp}");
+                let size = self.gen.sizes.size(element);
+                let align = self.gen.sizes.align(element);
+                uwriteln!(
+                    self.src,
+                    "let {iter} = wit_bindgen_guest_rust::rt::SizedIter::new({});
+                    let len{tmp} = {iter}.len() as i32;
+                    let {layout} = alloc::Layout::from_size_align_unchecked({iter}.len() * {size}, {align});",
+                    operands[0],
+                );
+                self.alloc_list(&base, &layout);
+                uwriteln!(
+                    self.src,
+                    "for (i, e) in {iter}.enumerate() {{
+                        core::ptr::write(({base} as usize + i * {size}) as *mut _, e);
+                    }}"
+                );
+                results.push(format!("{base} as i32"));
+                results.push(format!("len{tmp}"));
+                self.cleanup.push((base, layout));
+            }
+
+            Instruction::ListCanonLower {
+                element,
+                realloc: Some(_),
+            } if self.overaligned_record(element).is_some() => {
+                // The list's allocation has the overridden alignment, but the
+                // caller frees it with the canonical one, so it's copied into
+                // an allocation with the canonical layout instead.
+                let tmp = self.tmp();
+                let vec = format!("vec{tmp}");
+                let base = format!("base{tmp}");
+                let layout = format!("layout{tmp}");
+                let size = self.gen.sizes.size(element);
+                let align = self.gen.sizes.align(element);
+                let op0 = self.unwrap_list_newtype(&operands.pop().unwrap());
+                uwriteln!(
+                    self.src,
+                    "let {vec} = {op0};
+                    let len{tmp} = {vec}.len() as i32;
+                    let {layout} = alloc::Layout::from_size_align_unchecked({vec}.len() * {size}, {align});"
+                );
+                self.alloc_list(&base, &layout);
+                uwriteln!(
+                    self.src,
+                    "core::ptr::copy_nonoverlapping({vec}.as_ptr() as *const u8, {base}, {layout}.size());"
+                );
+                results.push(format!("{base} as i32"));
+                results.push(format!("len{tmp}"));
+            }
+
             Instruction::ListCanonLower { realloc, .. } => {
                 let tmp = self.tmp();
                 let val = format!("vec{}", tmp);
@@ -1276,7 +3470,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 if realloc.is_none() {
                     self.push_str(&format!("let {} = {};\n", val, operands[0]));
                 } else {
-                    let op0 = operands.pop().unwrap();
+                    let op0 = self.unwrap_list_newtype(&operands.pop().unwrap());
                     self.push_str(&format!("let {} = ({}).into_boxed_slice();\n", val, op0));
                 }
                 self.push_str(&format!("let {} = {}.as_ptr() as i32;\n", ptr, val));
@@ -1288,15 +3482,60 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 results.push(len);
             }
 
-            Instruction::ListCanonLift { .. } => {
+            Instruction::ListCanonLift { element, .. } if self.lifts_into_out() => {
+                let tmp = self.tmp();
+                let len = format!("len{tmp}");
+                let ptr = &operands[0];
+                self.push_str(&format!("let {len} = {} as usize;\n", operands[1]));
+                self.check_list_len(&len);
+                let size = self.gen.sizes.size(element);
+                let align = self.gen.sizes.align(element);
+                self.check_list_bounds(ptr, &len, size);
+                if self.overaligned_record(element).is_some() {
+                    uwriteln!(self.src, "out.clear();\nout.reserve({len});");
+                    self.copy_list_into(ptr, &len, size, "out");
+                } else {
+                    uwriteln!(
+                        self.src,
+                        "out.clear();
+                        out.extend_from_slice(core::slice::from_raw_parts({ptr} as *const _, {len}));"
+                    );
+                }
+                self.push_str(&format!(
+                    "wit_bindgen_guest_rust::rt::dealloc({ptr}, {len} * {size}, {align});\n",
+                ));
+                results.push("()".to_string());
+            }
+
+            Instruction::ListCanonLift { element, ty, .. } => {
                 let tmp = self.tmp();
                 let len = format!("len{}", tmp);
                 self.push_str(&format!("let {} = {} as usize;\n", len, operands[1]));
-                let result = format!(
-                    "Vec::from_raw_parts({} as *mut _, {1}, {1})",
-                    operands[0], len
-                );
-                results.push(result);
+                self.check_list_len(&len);
+                let size = self.gen.sizes.size(element);
+                self.check_list_bounds(&operands[0], &len, size);
+                let result = if let Some(id) = self.overaligned_record(element) {
+                    let vec = format!("vec{tmp}");
+                    let record = self.typename_lift(id);
+                    let align = self.gen.sizes.align(element);
+                    uwriteln!(
+                        self.src,
+                        "let mut {vec} = Vec::<{record}>::with_capacity({len});"
+                    );
+                    self.copy_list_into(&operands[0], &len, size, &vec);
+                    uwriteln!(
+                        self.src,
+                        "wit_bindgen_guest_rust::rt::dealloc({}, {len} * {size}, {align});",
+                        operands[0],
+                    );
+                    vec
+                } else {
+                    format!(
+                        "Vec::from_raw_parts({} as *mut _, {1}, {1})",
+                        operands[0], len
+                    )
+                };
+                results.push(self.wrap_list_newtype(*ty, result));
             }
 
             Instruction::StringLower { realloc } => {
@@ -1304,7 +3543,11 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 let val = format!("vec{}", tmp);
                 let ptr = format!("ptr{}", tmp);
                 let len = format!("len{}", tmp);
-                if realloc.is_none() {
+                let utf16 = matches!(self.gen.gen.opts.string_encoding, StringEncoding::UTF16);
+                if utf16 {
+                    let units = format!("{}.encode_utf16().collect::<Vec<u16>>()", operands[0]);
+                    self.push_str(&format!("let {} = {}.into_boxed_slice();\n", val, units));
+                } else if realloc.is_none() {
                     self.push_str(&format!("let {} = {};\n", val, operands[0]));
                 } else {
                     let op0 = format!("{}.into_bytes()", operands[0]);
@@ -1312,7 +3555,17 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 }
                 self.push_str(&format!("let {} = {}.as_ptr() as i32;\n", ptr, val));
                 self.push_str(&format!("let {} = {}.len() as i32;\n", len, val));
-                if realloc.is_some() {
+                if utf16 && realloc.is_none() {
+                    // The transcoded units may be lowered in a nested block,
+                    // so rather than dropping them at the end of it they're
+                    // freed along with the other allocations after the call.
+                    let layout = format!("layout{}", tmp);
+                    self.push_str(&format!(
+                        "let {layout} = alloc::Layout::from_size_align_unchecked({len} as usize * 2, 2);\n",
+                    ));
+                    self.push_str(&format!("core::mem::forget({});\n", val));
+                    self.cleanup.push((format!("{ptr} as *mut u8"), layout));
+                } else if realloc.is_some() {
                     self.push_str(&format!("core::mem::forget({});\n", val));
                 }
                 results.push(ptr);
@@ -1323,6 +3576,16 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 let tmp = self.tmp();
                 let len = format!("len{}", tmp);
                 self.push_str(&format!("let {} = {} as usize;\n", len, operands[1]));
+                self.check_list_len(&len);
+                if matches!(self.gen.gen.opts.string_encoding, StringEncoding::UTF16) {
+                    self.check_list_bounds(&operands[0], &len, 2);
+                    results.push(format!(
+                        "String::from_utf16(&Vec::from_raw_parts({} as *mut u16, {1}, {1})).unwrap()",
+                        operands[0], len
+                    ));
+                    return;
+                }
+                self.check_list_bounds(&operands[0], &len, 1);
                 let result = format!(
                     "Vec::from_raw_parts({} as *mut _, {1}, {1})",
                     operands[0], len
@@ -1343,23 +3606,50 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 let result = format!("result{tmp}");
                 let layout = format!("layout{tmp}");
                 let len = format!("len{tmp}");
-                self.push_str(&format!(
-                    "let {vec} = {operand0};\n",
-                    operand0 = operands[0]
-                ));
+                let operand0 = match realloc {
+                    Some(_) => self.unwrap_list_newtype(&operands[0]),
+                    // `SizedIter` has a `len` and an `into_iter` like the
+                    // slice it stands in for, so the rest is unchanged.
+                    None if self.is_iter_param(&operands[0]) => format!(
+                        "wit_bindgen_guest_rust::rt::SizedIter::new({})",
+                        operands[0]
+                    ),
+                    None => operands[0].clone(),
+                };
+                self.push_str(&format!("let {vec} = {operand0};\n"));
                 self.push_str(&format!("let {len} = {vec}.len() as i32;\n"));
                 let size = self.gen.sizes.size(element);
                 let align = self.gen.sizes.align(element);
-                self.push_str(&format!(
-                    "let {layout} = alloc::Layout::from_size_align_unchecked({vec}.len() * {size}, {align});\n",
-                ));
-                self.push_str(&format!(
-                    "let {result} = if {layout}.size() != 0\n{{\nlet ptr = alloc::alloc({layout});\n",
-                ));
-                self.push_str(&format!(
-                    "if ptr.is_null()\n{{\nalloc::handle_alloc_error({layout});\n}}\nptr\n}}",
-                ));
-                self.push_str(&format!("else {{\ncore::ptr::null_mut()\n}};\n",));
+                // Only lists lowered outside of any block live as long as the
+                // call, so nested lists can't use a buffer on the stack.
+                let inline = match self.gen.gen.opts.inline_list_capacity {
+                    Some(cap) if realloc.is_none() && self.block_storage.is_empty() => Some(cap),
+                    _ => None,
+                };
+                if let Some(cap) = inline {
+                    let bytes = cap * size;
+                    uwriteln!(
+                        self.src,
+                        "if {vec}.len() > {cap} {{
+                            panic!(\"list length {{}} exceeds the inline capacity of {cap}\", {vec}.len());
+                        }}
+                        #[repr(align({align}))]
+                        struct ListBuf{tmp}([u8; {bytes}]);
+                        let mut buf{tmp} = core::mem::MaybeUninit::<ListBuf{tmp}>::uninit();
+                        let {result} = buf{tmp}.as_mut_ptr() as *mut u8;"
+                    );
+                } else {
+                    self.push_str(&format!(
+                        "let {layout} = alloc::Layout::from_size_align_unchecked({vec}.len() * {size}, {align});\n",
+                    ));
+                    self.push_str(&format!(
+                        "let {result} = if {layout}.size() != 0\n{{\nlet ptr = alloc::alloc({layout});\n",
+                    ));
+                    self.push_str(&format!(
+                        "if ptr.is_null()\n{{\nalloc::handle_alloc_error({layout});\n}}\nptr\n}}",
+                    ));
+                    self.push_str(&format!("else {{\ncore::ptr::null_mut()\n}};\n",));
+                }
                 self.push_str(&format!("for (i, e) in {vec}.into_iter().enumerate() {{\n",));
                 self.push_str(&format!(
                     "let base = {result} as i32 + (i as i32) * {size};\n",
@@ -1369,7 +3659,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 results.push(format!("{result} as i32"));
                 results.push(len);
 
-                if realloc.is_none() {
+                if realloc.is_none() && inline.is_none() {
                     // If an allocator isn't requested then we must clean up the
                     // allocation ourselves since our callee isn't taking
                     // ownership.
@@ -1377,7 +3667,7 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 }
             }
 
-            Instruction::ListLift { element, .. } => {
+            Instruction::ListLift { element, ty, .. } => {
                 let body = self.blocks.pop().unwrap();
                 let tmp = self.tmp();
                 let size = self.gen.sizes.size(element);
@@ -1393,9 +3683,17 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                     "let {len} = {operand1};\n",
                     operand1 = operands[1]
                 ));
-                self.push_str(&format!(
-                    "let mut {result} = Vec::with_capacity({len} as usize);\n",
-                ));
+                self.check_list_len(&len);
+                self.check_list_bounds(&base, &len, size);
+                let into_out = self.lifts_into_out();
+                if into_out {
+                    self.push_str(&format!("out.clear();\nout.reserve({len} as usize);\n"));
+                } else {
+                    self.push_str(&format!(
+                        "let mut {result} = Vec::with_capacity({len} as usize);\n",
+                    ));
+                }
+                let vec = if into_out { "out" } else { &result };
 
                 self.push_str("for i in 0..");
                 self.push_str(&len);
@@ -1405,12 +3703,13 @@ impl Bindgen for FunctionBindgen<'_, '_> {
                 self.push_str(" + i *");
                 self.push_str(&size.to_string());
                 self.push_str(";\n");
-                self.push_str(&result);
-                self.push_str(".push(");
-                self.push_str(&body);
-                self.push_str(");\n");
+                self.push_str(&format!("{vec}.push({body});\n"));
                 self.push_str("}\n");
-                results.push(result);
+                results.push(if into_out {
+                    "()".to_string()
+                } else {
+                    self.wrap_list_newtype(*ty, result)
+                });
                 self.push_str(&format!(
                     "wit_bindgen_guest_rust::rt::dealloc({base}, ({len} as usize) * {size}, {align});\n",
                 ));
@@ -1441,14 +3740,15 @@ impl Bindgen for FunctionBindgen<'_, '_> {
 
             Instruction::CallInterface { func, .. } => {
                 self.let_results(func.results.len(), results);
-                match &func.kind {
-                    FunctionKind::Freestanding => {
-                        self.push_str(&format!("T::{}", to_rust_ident(&func.name)));
-                    }
+                let name = match &func.kind {
+                    FunctionKind::Freestanding => to_rust_ident(&func.name),
+                };
+                let args = self.export_call_args(func, operands);
+                if self.gen.gen.opts.export_context_type.is_some() {
+                    self.push_str(&format!("T::with_context(|ctx| T::{name}(ctx, {args}))"));
+                } else {
+                    self.push_str(&format!("T::{name}({args})"));
                 }
-                self.push_str("(");
-                self.push_str(&operands.join(", "));
-                self.push_str(")");
                 self.push_str(";\n");
             }
 
@@ -1551,8 +3851,12 @@ impl Bindgen for FunctionBindgen<'_, '_> {
             }
 
             Instruction::GuestDeallocateString => {
+                let unit = match self.gen.gen.opts.string_encoding {
+                    StringEncoding::UTF16 => 2,
+                    _ => 1,
+                };
                 self.push_str(&format!(
-                    "wit_bindgen_guest_rust::rt::dealloc({}, ({}) as usize, 1);\n",
+                    "wit_bindgen_guest_rust::rt::dealloc({}, ({}) as usize * {unit}, {unit});\n",
                     operands[0], operands[1],
                 ));
             }
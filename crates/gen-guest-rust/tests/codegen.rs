@@ -153,3 +153,1815 @@ mod skip {
 
     export_baz!(Component);
 }
+
+mod impl_into_iterator {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                export exports: interface {
+                    record names {
+                        items: list<string>,
+                    }
+
+                    record not-a-list {
+                        a: list<u8>,
+                        b: list<u8>,
+                    }
+
+                    foo: func() -> names
+                    bar: func(x: not-a-list)
+                }
+            }
+        ",
+        impl_into_iterator,
+    });
+
+    #[allow(dead_code)]
+    fn test(names: exports::Names) {
+        for _name in &names {}
+        let _v: Vec<String> = names.into_iter().collect();
+    }
+}
+
+mod c_error_codes {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    enum errno {
+                        again,
+                        io,
+                    }
+
+                    foo: func() -> result<u32, errno>
+                }
+            }
+        ",
+        c_error_codes,
+    });
+
+    #[allow(dead_code)]
+    fn test() -> i32 {
+        assert_eq!(imports::Errno::Again.to_errno(), -1);
+        assert_eq!(imports::Errno::Io.to_errno(), -2);
+        match imports::foo() {
+            Ok(n) => n as i32,
+            Err(e) => e.to_errno(),
+        }
+    }
+}
+
+mod safe_enum_lift {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    enum color {
+                        red,
+                        green,
+                    }
+
+                    variant shape {
+                        circle,
+                        square,
+                    }
+
+                    foo: func() -> color
+                    bar: func() -> shape
+                }
+            }
+        ",
+        unchecked,
+        safe_enum_lift,
+    });
+
+    #[test]
+    fn works() {}
+}
+
+mod allocator_path {
+    pub mod arena {
+        pub use std::alloc::{handle_alloc_error, Layout};
+
+        pub unsafe fn alloc(layout: Layout) -> *mut u8 {
+            std::alloc::alloc(layout)
+        }
+
+        pub unsafe fn dealloc(ptr: *mut u8, layout: Layout) {
+            std::alloc::dealloc(ptr, layout)
+        }
+    }
+
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    foo: func(x: list<string>)
+                }
+            }
+        ",
+        allocator_path: "crate::allocator_path::arena",
+    });
+
+    #[test]
+    fn works() {}
+}
+
+mod native_export_stubs {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                export exports: interface {
+                    foo: func(x: string) -> string
+                }
+            }
+        ",
+        native_export_stubs,
+    });
+
+    struct Component;
+
+    impl exports::Exports for Component {
+        fn foo(x: String) -> String {
+            x
+        }
+    }
+
+    export_baz!(Component);
+}
+
+mod acronyms {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                export json-rpc: interface {
+                    record http-request {
+                        body: list<u8>,
+                    }
+
+                    enum io-error {
+                        closed,
+                    }
+
+                    send: func(req: http-request) -> result<_, io-error>
+                }
+            }
+        ",
+        acronyms: ["json", "http", "io"],
+    });
+
+    struct Component;
+
+    impl json_rpc::JSONRpc for Component {
+        fn send(_req: json_rpc::HTTPRequest) -> Result<(), json_rpc::IOError> {
+            Err(json_rpc::IOError::Closed)
+        }
+    }
+
+    export_baz!(Component);
+}
+
+mod std_feature {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    enum errno {
+                        again,
+                    }
+
+                    foo: func() -> result<_, errno>
+                }
+            }
+        ",
+        no_std,
+        std_feature,
+    });
+
+    #[allow(dead_code)]
+    fn test(e: imports::Errno) -> String {
+        // `Display` is available regardless of the `std` feature.
+        format!("{e}")
+    }
+}
+
+mod std_feature_alloc {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    record failure {
+                        message: string,
+                        codes: list<u32>,
+                    }
+
+                    foo: func(name: string) -> result<list<string>, failure>
+                }
+
+                export exports: interface {
+                    record failure {
+                        message: string,
+                    }
+
+                    bar: func(names: list<string>) -> result<string, failure>
+                }
+            }
+        ",
+        no_std,
+        std_feature,
+    });
+
+    struct Component;
+
+    impl exports::Exports for Component {
+        fn bar(names: Vec<String>) -> Result<String, exports::Failure> {
+            names.into_iter().next().ok_or(exports::Failure {
+                message: String::from("no names"),
+            })
+        }
+    }
+
+    export_baz!(Component);
+
+    #[allow(dead_code)]
+    fn test() -> String {
+        match imports::foo("x") {
+            Ok(names) => names.concat(),
+            // `Display` is available regardless of the `std` feature.
+            Err(e) => format!("{e}"),
+        }
+    }
+}
+
+mod prelude {
+    mod bindings {
+        wit_bindgen_guest_rust::generate!({
+            inline: "
+                default world baz {
+                    export exports: interface {
+                        record point {
+                            x: u32,
+                            y: u32,
+                        }
+
+                        foo: func(p: list<point>) -> option<point>
+                    }
+                }
+            ",
+            macro_call_prefix: "bindings::",
+            prelude,
+        });
+
+        pub(crate) use export_baz;
+    }
+
+    use bindings::prelude::*;
+
+    struct Component;
+
+    impl Exports for Component {
+        fn foo(p: Vec<Point>) -> Option<Point> {
+            p.into_iter().next()
+        }
+    }
+
+    bindings::export_baz!(Component);
+}
+
+mod debug {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world debug-output {
+                import imports: interface {
+                    foo: func()
+                }
+            }
+        ",
+        debug,
+    });
+
+    #[test]
+    fn works() {
+        let dir = option_env!("OUT_DIR")
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(std::env::temp_dir);
+        assert!(dir.join("wit-bindgen-debug-debug-output.rs").exists());
+    }
+}
+
+mod native_import_trap {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    foo: func(x: string) -> u32
+                }
+            }
+        ",
+        native_import_trap,
+    });
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    #[should_panic(expected = "import `imports::foo` is not available on this target")]
+    fn traps() {
+        imports::foo("x");
+    }
+}
+
+mod max_list_len {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    foo: func() -> list<string>
+                    bar: func() -> list<u8>
+                }
+            }
+        ",
+        max_list_len: 1024,
+    });
+
+    #[test]
+    fn works() {}
+}
+
+mod visibility {
+    mod bindings {
+        wit_bindgen_guest_rust::generate!({
+            inline: "
+                default world baz {
+                    import imports: interface {
+                        foo: func()
+                    }
+                }
+            ",
+            visibility: "pub(crate)",
+        });
+    }
+
+    #[allow(dead_code)]
+    fn test() {
+        bindings::imports::foo();
+    }
+}
+
+mod derive_eq {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                export exports: interface {
+                    record point {
+                        x: u32,
+                        name: string,
+                    }
+
+                    record sample {
+                        value: float64,
+                    }
+
+                    variant shape {
+                        circle(point),
+                        none,
+                    }
+
+                    foo: func(p: point, s: sample) -> shape
+                }
+            }
+        ",
+        derive_eq,
+    });
+
+    use exports::{Point, Sample, Shape};
+
+    fn assert_eq<T: Eq>() {}
+
+    #[test]
+    fn works() {
+        assert_eq::<Point>();
+        assert_eq::<Shape>();
+
+        let p = Point {
+            x: 1,
+            name: "a".to_string(),
+        };
+        assert_eq!(Shape::Circle(p.clone()), Shape::Circle(p));
+        assert!(Sample { value: 1.0 } == Sample { value: 1.0 });
+    }
+}
+
+mod emit_layout_consts {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    record point {
+                        x: u8,
+                        y: u64,
+                    }
+
+                    enum color {
+                        red,
+                        green,
+                    }
+
+                    foo: func(p: point, c: color)
+                }
+            }
+        ",
+        emit_layout_consts,
+    });
+
+    #[test]
+    fn works() {
+        assert_eq!(imports::Point::ABI_SIZE, 16);
+        assert_eq!(imports::Point::ABI_ALIGN, 8);
+        assert_eq!(imports::Color::ABI_SIZE, 1);
+        assert_eq!(imports::Color::ABI_ALIGN, 1);
+    }
+}
+
+mod doc_aliases {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                export exports: interface {
+                    record http-request {
+                        body: list<u8>,
+                    }
+
+                    flags access-mode {
+                        read,
+                        write,
+                    }
+
+                    send-request: func(req: http-request, mode: access-mode)
+                }
+            }
+        ",
+        doc_aliases,
+    });
+
+    struct Component;
+
+    impl exports::Exports for Component {
+        fn send_request(_req: exports::HttpRequest, _mode: exports::AccessMode) {}
+    }
+
+    export_baz!(Component);
+}
+
+mod list_of_options {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    record point {
+                        x: u32,
+                        y: u64,
+                    }
+
+                    foo: func() -> list<option<point>>
+                    bar: func(x: list<option<u8>>) -> list<option<string>>
+                }
+            }
+        ",
+    });
+
+    #[allow(dead_code)]
+    fn test() {
+        let _p: Vec<Option<imports::Point>> = imports::foo();
+        let _s: Vec<Option<String>> = imports::bar(&[Some(1), None]);
+    }
+}
+
+mod extern_abi {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    foo: func(x: string) -> u32
+                }
+                export exports: interface {
+                    bar: func(x: string) -> string
+                }
+            }
+        ",
+        extern_abi: "C-unwind",
+    });
+
+    #[test]
+    fn rejects_unknown_abi() {
+        use wit_bindgen_core::wit_parser::{Resolve, UnresolvedPackage};
+
+        let mut resolve = Resolve::default();
+        let pkg = UnresolvedPackage::parse(
+            "extern-abi".as_ref(),
+            "default world baz { import foo: func() }",
+        )
+        .unwrap();
+        resolve.push(pkg, &Default::default()).unwrap();
+        let (world, _) = resolve.worlds.iter().next().unwrap();
+
+        let err = wit_bindgen_gen_guest_rust::Opts {
+            extern_abi: Some("stdcall".to_string()),
+            ..Default::default()
+        }
+        .build()
+        .generate(&resolve, world, &mut Default::default())
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown extern ABI `stdcall`"));
+    }
+
+    struct Component;
+
+    impl exports::Exports for Component {
+        fn bar(x: String) -> String {
+            x
+        }
+    }
+
+    export_baz!(Component);
+}
+
+mod expose_raw_imports {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    foo: func(x: u32, y: float64) -> u64
+                    bar: func(x: string)
+                }
+            }
+        ",
+        expose_raw_imports,
+    });
+
+    #[allow(dead_code)]
+    fn test() {
+        let _f: unsafe extern "C" fn(i32, f64) -> i64 = imports::raw::foo;
+        let _b: unsafe extern "C" fn(i32, i32) = imports::raw::bar;
+    }
+}
+
+mod unit_result_as_status {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    type outcome = result
+
+                    foo: func() -> result
+                    bar: func(x: outcome, y: result<u32>) -> result<u32>
+                }
+                export exports: interface {
+                    baz: func(x: result) -> result
+                }
+            }
+        ",
+        unit_result_as_status,
+    });
+
+    use wit_bindgen_guest_rust::Status;
+
+    struct Component;
+
+    impl exports::Exports for Component {
+        fn baz(x: Status) -> Status {
+            x
+        }
+    }
+
+    export_baz!(Component);
+
+    #[allow(dead_code)]
+    fn test() {
+        let _s: Status = imports::foo();
+        let _o: imports::Outcome = Status::Ok;
+        let _r: Result<u32, ()> = imports::bar(Status::Err, Ok(1));
+    }
+}
+
+mod list_into_buffer {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    foo: func(x: u32) -> list<string>
+                    bar: func() -> list<u8>
+                    baz: func() -> list<list<u32>>
+                    not-a-list: func() -> option<list<u8>>
+                }
+            }
+        ",
+        list_into_buffer,
+    });
+
+    #[allow(dead_code)]
+    fn test() {
+        let mut strings = Vec::new();
+        imports::foo_into(1, &mut strings);
+        let mut bytes = Vec::new();
+        imports::bar_into(&mut bytes);
+        let mut nested: Vec<Vec<u32>> = Vec::new();
+        imports::baz_into(&mut nested);
+        let _o: Option<Vec<u8>> = imports::not_a_list();
+    }
+}
+
+mod wasm_only_externs {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    foo: func(x: string) -> u32
+                }
+            }
+        ",
+        wasm_only_externs,
+    });
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    #[should_panic(expected = "import `imports::foo` is not available on this target")]
+    fn traps() {
+        imports::foo("x");
+    }
+}
+
+mod enum_str {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    enum log-level {
+                        debug,
+                        very-verbose,
+                    }
+
+                    enum errno {
+                        again,
+                    }
+
+                    foo: func(l: log-level) -> result<_, errno>
+                }
+            }
+        ",
+        enum_str,
+    });
+
+    use imports::{Errno, LogLevel};
+
+    #[test]
+    fn works() {
+        assert!(matches!("very-verbose".parse(), Ok(LogLevel::VeryVerbose)));
+        assert!("VeryVerbose".parse::<LogLevel>().is_err());
+        assert_eq!(LogLevel::Debug.to_string(), "debug");
+        assert!(matches!("again".parse(), Ok(Errno::Again)));
+    }
+}
+
+mod enum_hash {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    enum color {
+                        red,
+                        green,
+                    }
+
+                    variant state {
+                        idle,
+                        busy,
+                    }
+
+                    foo: func(c: color, s: state)
+                }
+            }
+        ",
+    });
+
+    use imports::{Color, State};
+    use std::collections::HashMap;
+
+    #[test]
+    fn works() {
+        let mut colors = HashMap::new();
+        colors.insert(Color::Red, 1);
+        colors.insert(Color::Green, 2);
+        assert_eq!(colors[&Color::Red], 1);
+
+        let mut states = HashMap::new();
+        states.insert(State::Idle, "idle");
+        assert_eq!(states.get(&State::Busy), None);
+    }
+}
+
+mod import_module_aliases {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    foo: func()
+                }
+            }
+        ",
+        import_module_aliases: { "imports": "legacy-imports" },
+        native_import_trap,
+    });
+
+    #[test]
+    #[cfg(not(target_arch = "wasm32"))]
+    #[should_panic(expected = "import `legacy-imports::foo` is not available on this target")]
+    fn works() {
+        imports::foo();
+    }
+}
+
+mod tuples {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                export exports: interface {
+                    type pair = tuple<u32, string>
+
+                    record entry {
+                        key: tuple<u32, string>,
+                    }
+
+                    variant item {
+                        pair(pair),
+                        nested(tuple<tuple<u8, u64>, list<tuple<u32, string>>>),
+                    }
+
+                    foo: func(x: list<tuple<u32, string>>) -> list<tuple<u32, string>>
+                    bar: func(e: entry, i: item) -> tuple<entry, item>
+                }
+            }
+        ",
+    });
+
+    use exports::{Entry, Item, Pair};
+
+    struct Component;
+
+    impl exports::Exports for Component {
+        fn foo(x: Vec<(u32, String)>) -> Vec<(u32, String)> {
+            x
+        }
+
+        fn bar(e: Entry, i: Item) -> (Entry, Item) {
+            (e, i)
+        }
+    }
+
+    export_baz!(Component);
+
+    #[allow(dead_code)]
+    fn test() -> Item {
+        let _p: Pair = (1, String::new());
+        Item::Nested(((1, 2), vec![(3, String::new())]))
+    }
+}
+
+#[deny(unsafe_code)]
+mod unsafe_module {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    foo: func(x: string) -> list<u8>
+                }
+                export exports: interface {
+                    bar: func(x: string) -> string
+                }
+            }
+        ",
+        unsafe_module,
+    });
+
+    struct Component;
+
+    impl exports::Exports for Component {
+        fn bar(x: String) -> String {
+            x
+        }
+    }
+
+    export_baz!(Component);
+
+    #[allow(dead_code)]
+    fn test() -> Vec<u8> {
+        imports::foo("x")
+    }
+}
+
+mod c_tagged_variants {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    record point {
+                        x: u32,
+                        y: u32,
+                    }
+
+                    variant shape {
+                        none,
+                        circle(float32),
+                        rect(point),
+                    }
+
+                    variant not-c {
+                        name(string),
+                    }
+
+                    foo: func(s: shape, n: not-c)
+                }
+            }
+        ",
+        c_tagged_variants,
+    });
+
+    use imports::{Point, Shape, ShapeC};
+
+    #[test]
+    fn works() {
+        let c = ShapeC::from(Shape::Rect(Point { x: 1, y: 2 }));
+        assert_eq!(c.tag, 2);
+        assert_eq!(c.rect().map(|p| p.y), Some(2));
+        assert_eq!(c.circle(), None);
+        assert!(ShapeC::from(Shape::None).is_none());
+    }
+}
+
+#[deny(non_camel_case_types)]
+mod preserve_wit_case {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                export exports: interface {
+                    record http-request {
+                        body: list<u8>,
+                    }
+
+                    enum match {
+                        get,
+                    }
+
+                    flags access-mode {
+                        read,
+                    }
+
+                    type status-code = u16
+
+                    send: func(req: http-request, t: match, m: access-mode) -> status-code
+                }
+            }
+        ",
+        preserve_wit_case,
+    });
+
+    use exports::{access_mode, http_request, match_, status_code};
+
+    struct Component;
+
+    impl exports::exports for Component {
+        fn send(_req: http_request, _t: match_, _m: access_mode) -> status_code {
+            200
+        }
+    }
+
+    export_baz!(Component);
+}
+
+mod box_large_variants {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    record big {
+                        a: u64, b: u64, c: u64, d: u64,
+                        e: u64, f: u64, g: u64, h: u64,
+                    }
+
+                    variant message {
+                        small(u8),
+                        large(big),
+                        text(string),
+                    }
+
+                    record envelope {
+                        m: message,
+                    }
+
+                    foo: func(m: message, l: list<message>) -> message
+                    bar: func(e: envelope) -> list<envelope>
+                }
+                export exports: interface {
+                    variant reply {
+                        small(u8),
+                        large(tuple<u64, u64, u64, u64, u64, u64, u64, u64>),
+                        text(string),
+                    }
+
+                    baz: func(r: reply) -> reply
+                }
+            }
+        ",
+        box_large_variants: 32,
+    });
+
+    use imports::{Big, MessageResult as Message};
+
+    struct Component;
+
+    impl exports::Exports for Component {
+        fn baz(r: exports::Reply) -> exports::Reply {
+            r
+        }
+    }
+
+    export_baz!(Component);
+
+    #[test]
+    fn works() {
+        assert!(std::mem::size_of::<Message>() < std::mem::size_of::<Big>());
+        let big = Big {
+            a: 0,
+            b: 0,
+            c: 0,
+            d: 0,
+            e: 0,
+            f: 0,
+            g: 0,
+            h: 0,
+        };
+        let _m = Message::Large(Box::new(big));
+        let _s = Message::Small(1);
+    }
+}
+
+mod doc_abi_details {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    /// Returns the current time.
+                    now: func() -> u64
+                    sleep: func(ms: u32)
+                }
+            }
+        ",
+        doc_abi_details,
+    });
+
+    #[allow(dead_code)]
+    fn call_imports() {
+        imports::sleep(imports::now() as u32);
+    }
+}
+
+mod split_raw_wrappers {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    record point {
+                        x: u32,
+                        y: u32,
+                    }
+
+                    distance: func(a: point, b: point) -> float64
+                    names: func(prefix: string) -> list<string>
+                }
+            }
+        ",
+        split_raw_wrappers,
+        list_into_buffer,
+    });
+
+    #[allow(dead_code)]
+    fn call_imports() {
+        let a = imports::Point { x: 0, y: 0 };
+        let b = imports::Point { x: 3, y: 4 };
+        let _: f64 = imports::distance(a, b) + imports::distance_raw(a, b);
+        let mut out = Vec::new();
+        imports::names_raw_into("x", &mut out);
+        imports::names_into("x", &mut out);
+        let _: Vec<String> = imports::names("x");
+    }
+}
+
+mod debug_list_bounds {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    foo: func() -> list<list<u8>>
+                    bar: func() -> list<string>
+                }
+
+                export exports: interface {
+                    baz: func(a: list<u32>, b: string, c: list<list<u8>>)
+                }
+            }
+        ",
+        debug_list_bounds,
+    });
+
+    struct Component;
+
+    impl exports::Exports for Component {
+        fn baz(_a: Vec<u32>, _b: String, _c: Vec<Vec<u8>>) {}
+    }
+
+    export_baz!(Component);
+
+    #[test]
+    fn check_list_bounds() {
+        wit_bindgen_guest_rust::rt::check_list_bounds(8, 4, 4);
+        let overflow = std::panic::catch_unwind(|| {
+            wit_bindgen_guest_rust::rt::check_list_bounds(8, usize::MAX, 4);
+        });
+        assert_eq!(overflow.is_err(), cfg!(debug_assertions));
+    }
+}
+
+mod type_attribute_macros {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                export exports: interface {
+                    record point {
+                        x: u32,
+                        y: u32,
+                    }
+
+                    variant shape {
+                        dot(point),
+                        line(tuple<point, point>),
+                    }
+
+                    enum color {
+                        red,
+                        green,
+                    }
+
+                    flags style {
+                        bold,
+                        italic,
+                    }
+
+                    draw: func(s: shape, c: color, f: style) -> point
+                }
+            }
+        ",
+        type_attribute_macros: ["must_use", "allow(clippy::all)"],
+    });
+
+    struct Component;
+
+    impl exports::Exports for Component {
+        fn draw(_s: exports::Shape, _c: exports::Color, _f: exports::Style) -> exports::Point {
+            exports::Point { x: 0, y: 0 }
+        }
+    }
+
+    export_baz!(Component);
+}
+
+mod export_context_type {
+    use std::cell::RefCell;
+
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                export exports: interface {
+                    add: func(n: u32) -> u32
+                    reset: func()
+                }
+
+                export total: func() -> u32
+            }
+        ",
+        export_context_type: "crate::export_context_type::Counter",
+    });
+
+    #[derive(Default)]
+    pub struct Counter {
+        total: u32,
+    }
+
+    thread_local! {
+        static COUNTER: RefCell<Counter> = RefCell::default();
+    }
+
+    struct Component;
+
+    impl Baz for Component {
+        fn total(ctx: &mut Counter) -> u32 {
+            ctx.total
+        }
+
+        fn with_context<R>(f: impl FnOnce(&mut Counter) -> R) -> R {
+            COUNTER.with(|c| f(&mut c.borrow_mut()))
+        }
+    }
+
+    impl exports::Exports for Component {
+        fn add(ctx: &mut Counter, n: u32) -> u32 {
+            ctx.total += n;
+            ctx.total
+        }
+
+        fn reset(ctx: &mut Counter) {
+            ctx.total = 0;
+        }
+
+        fn with_context<R>(f: impl FnOnce(&mut Counter) -> R) -> R {
+            <Component as Baz>::with_context(f)
+        }
+    }
+
+    export_baz!(Component);
+}
+
+mod record_align_overrides {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    record vec4 {
+                        x: float32,
+                        y: float32,
+                        z: float32,
+                        w: float32,
+                    }
+
+                    load: func() -> list<vec4>
+                }
+
+                export exports: interface {
+                    record vec4 {
+                        x: float32,
+                        y: float32,
+                        z: float32,
+                        w: float32,
+                    }
+
+                    sum: func(v: list<vec4>) -> vec4
+                    splat: func(n: u32) -> list<vec4>
+                }
+            }
+        ",
+        record_align_overrides: { "vec4": 16 },
+        list_into_buffer,
+    });
+
+    struct Component;
+
+    impl exports::Exports for Component {
+        fn sum(v: Vec<exports::Vec4>) -> exports::Vec4 {
+            let mut ret = exports::Vec4 {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+                w: 0.0,
+            };
+            for v in v {
+                ret.x += v.x;
+                ret.y += v.y;
+                ret.z += v.z;
+                ret.w += v.w;
+            }
+            ret
+        }
+
+        fn splat(n: u32) -> Vec<exports::Vec4> {
+            (0..n)
+                .map(|i| {
+                    let i = i as f32;
+                    exports::Vec4 {
+                        x: i,
+                        y: i,
+                        z: i,
+                        w: i,
+                    }
+                })
+                .collect()
+        }
+    }
+
+    export_baz!(Component);
+
+    #[allow(dead_code)]
+    fn call_imports() -> Vec<imports::Vec4> {
+        let mut out = Vec::new();
+        imports::load_into(&mut out);
+        imports::load()
+    }
+
+    #[test]
+    fn layout() {
+        assert_eq!(std::mem::align_of::<imports::Vec4>(), 16);
+        assert_eq!(std::mem::size_of::<exports::Vec4>(), 16);
+    }
+}
+
+mod newtype_list_aliases {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    type bytes = list<u8>
+                    type names = list<string>
+
+                    read: func(len: u32) -> bytes
+                    write: func(data: bytes)
+                    list-names: func() -> names
+                }
+
+                export exports: interface {
+                    type bytes = list<u8>
+
+                    record chunk {
+                        data: bytes,
+                        parts: list<bytes>,
+                    }
+
+                    split: func(data: bytes) -> chunk
+                }
+            }
+        ",
+        newtype_list_aliases,
+    });
+
+    impl exports::Bytes {
+        fn checksum(&self) -> u8 {
+            self.iter().fold(0, |a, b| a.wrapping_add(*b))
+        }
+    }
+
+    struct Component;
+
+    impl exports::Exports for Component {
+        fn split(data: exports::Bytes) -> exports::Chunk {
+            let parts = data
+                .chunks(2)
+                .map(|c| exports::Bytes::from(c.to_vec()))
+                .collect();
+            exports::Chunk { data, parts }
+        }
+    }
+
+    export_baz!(Component);
+
+    #[allow(dead_code)]
+    fn call_imports() -> Vec<String> {
+        let data: imports::BytesResult = imports::read(4);
+        imports::write(&data);
+        let names: Vec<String> = imports::list_names().into();
+        names
+    }
+
+    #[test]
+    fn methods() {
+        let mut bytes = exports::Bytes::default();
+        bytes.push(1);
+        bytes.push(2);
+        assert_eq!(bytes.checksum(), 3);
+        assert_eq!(Vec::from(bytes), [1, 2]);
+    }
+}
+
+mod generic_export {
+    use std::marker::PhantomData;
+
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                export exports: interface {
+                    foo: func(x: u32) -> u32
+                }
+            }
+        ",
+    });
+
+    trait Scale {
+        const FACTOR: u32;
+    }
+
+    struct Double;
+
+    impl Scale for Double {
+        const FACTOR: u32 = 2;
+    }
+
+    struct Component<T>(PhantomData<T>);
+
+    impl<T: Scale> exports::Exports for Component<T> {
+        fn foo(x: u32) -> u32 {
+            x * T::FACTOR
+        }
+    }
+
+    export_baz!(Component<Double>);
+}
+
+mod type_name_prefix {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    record input-stream {
+                        id: u32,
+                    }
+
+                    enum mode {
+                        read,
+                        write,
+                    }
+
+                    flags access {
+                        exclusive,
+                    }
+
+                    open: func(m: mode, a: access) -> input-stream
+                }
+
+                export exports: interface {
+                    variant event {
+                        closed,
+                        data(list<u8>),
+                    }
+
+                    poll: func() -> event
+                }
+            }
+        ",
+        type_name_prefix: "Wasi",
+    });
+
+    struct Component;
+
+    impl exports::Exports for Component {
+        fn poll() -> exports::WasiEvent {
+            exports::WasiEvent::Closed
+        }
+    }
+
+    export_baz!(Component);
+
+    #[allow(dead_code)]
+    fn call_imports() -> imports::WasiInputStream {
+        imports::open(imports::WasiMode::Read, imports::WasiAccess::EXCLUSIVE)
+    }
+}
+
+mod cold_trap_fns {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                export exports: interface {
+                    enum color {
+                        red,
+                        green,
+                    }
+
+                    variant shape {
+                        dot,
+                        circle(float32),
+                    }
+
+                    union number {
+                        u32,
+                        float64,
+                    }
+
+                    foo: func(
+                        a: bool,
+                        b: color,
+                        c: shape,
+                        d: number,
+                        e: option<u8>,
+                        f: result<u8, string>,
+                    )
+                }
+            }
+        ",
+        cold_trap_fns,
+    });
+
+    struct Component;
+
+    impl exports::Exports for Component {
+        fn foo(
+            _a: bool,
+            _b: exports::Color,
+            _c: exports::Shape,
+            _d: exports::Number,
+            _e: Option<u8>,
+            _f: Result<u8, String>,
+        ) {
+        }
+    }
+
+    export_baz!(Component);
+
+    #[test]
+    #[should_panic(expected = "invalid enum discriminant")]
+    fn panics() {
+        wit_bindgen_guest_rust::rt::invalid_discriminant("enum");
+    }
+}
+
+mod option_of_record_with_list {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    record entry {
+                        key: string,
+                        values: list<u32>,
+                    }
+
+                    lookup: func(key: string) -> option<entry>
+                    store: func(e: option<entry>)
+                }
+
+                export exports: interface {
+                    record entry {
+                        key: string,
+                        values: list<u32>,
+                    }
+
+                    roundtrip: func(e: option<entry>) -> option<entry>
+                }
+            }
+        ",
+    });
+
+    struct Component;
+
+    impl exports::Exports for Component {
+        fn roundtrip(e: Option<exports::Entry>) -> Option<exports::Entry> {
+            e
+        }
+    }
+
+    export_baz!(Component);
+
+    #[allow(dead_code)]
+    fn call_imports() {
+        if let Some(e) = imports::lookup("a") {
+            imports::store(Some(imports::EntryParam {
+                key: &e.key,
+                values: &e.values,
+            }));
+        }
+        imports::store(None);
+    }
+}
+
+mod abi_version_const {
+    mod v1 {
+        wit_bindgen_guest_rust::generate!({
+            inline: "
+                default world baz {
+                    import imports: interface {
+                        foo: func(x: u32) -> string
+                    }
+                }
+            ",
+            abi_version_const: "abi-version-v1",
+        });
+    }
+
+    mod v2 {
+        wit_bindgen_guest_rust::generate!({
+            inline: "
+                default world baz {
+                    import imports: interface {
+                        foo: func(x: u64) -> string
+                    }
+                }
+            ",
+            abi_version_const: "abi-version-v2",
+        });
+    }
+
+    mod v3 {
+        wit_bindgen_guest_rust::generate!({
+            inline: "
+                default world baz {
+                    import imports: interface {
+                        foo: func(x: s32) -> string
+                    }
+                }
+            ",
+            abi_version_const: "abi-version-v3",
+        });
+    }
+
+    #[test]
+    fn changes_with_signatures() {
+        assert_ne!(v1::ABI_VERSION, v2::ABI_VERSION);
+    }
+
+    #[test]
+    fn changes_with_wit_types() {
+        // `u32` and `s32` are both lowered to an `i32`.
+        assert_ne!(v1::ABI_VERSION, v3::ABI_VERSION);
+    }
+}
+
+mod string_encoding_utf16 {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    echo: func(s: string) -> string
+                    names: func() -> list<string>
+                    join: func(names: list<string>) -> string
+                    greet-maybe: func(name: option<string>)
+                }
+                export exports: interface {
+                    greet: func(name: string) -> string
+                }
+            }
+        ",
+        string_encoding: "utf16",
+    });
+
+    struct Component;
+
+    export_baz!(Component);
+
+    impl exports::Exports for Component {
+        fn greet(name: String) -> String {
+            let names = imports::names();
+            imports::greet_maybe(Some(&name));
+            let all = imports::join(&[&name, "world"]);
+            imports::echo(&format!("{all} and {}", names.len()))
+        }
+    }
+}
+
+mod list_params_impl_intoiterator {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    record point {
+                        x: u32,
+                        y: u32,
+                    }
+                    sum: func(a: list<u32>) -> u32
+                    join: func(a: list<string>, sep: string) -> string
+                    draw: func(a: list<point>, b: list<list<u8>>)
+                    read: func(a: list<u8>) -> list<u8>
+                }
+            }
+        ",
+        list_params_impl_intoiterator,
+        list_into_buffer,
+    });
+
+    #[allow(dead_code)]
+    fn call() {
+        imports::sum((0..10).map(|i| i * 2));
+        imports::join(["a", "b"], ",");
+        imports::draw([imports::Point { x: 1, y: 2 }], vec![&b"abc"[..]]);
+        let mut out = Vec::new();
+        imports::read_into(vec![1, 2, 3], &mut out);
+        imports::sum((0..10).filter(|i| i % 3 == 0));
+    }
+
+    #[test]
+    fn sized_iter() {
+        use wit_bindgen_guest_rust::rt::SizedIter;
+
+        let exact = SizedIter::new(vec![1, 2, 3]);
+        assert_eq!(exact.len(), 3);
+        let filtered = SizedIter::new((0..10).filter(|i| i % 3 == 0));
+        assert_eq!(filtered.len(), 4);
+        assert_eq!(filtered.collect::<Vec<_>>(), [0, 3, 6, 9]);
+    }
+}
+
+mod variant_accessors {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    variant shape {
+                        circle(u32),
+                        label(string),
+                        empty,
+                    }
+                    variant only {
+                        one(u8),
+                    }
+                    get: func() -> shape
+                    set: func(s: shape, o: only)
+                }
+            }
+        ",
+        variant_accessors,
+    });
+
+    #[test]
+    fn accessors() {
+        use imports::{Only, ShapeResult};
+
+        let circle = ShapeResult::Circle(3);
+        assert_eq!(circle.as_circle(), Some(&3));
+        assert_eq!(circle.as_label(), None);
+        assert!(!circle.is_empty());
+        assert!(ShapeResult::Empty.is_empty());
+        let label = ShapeResult::Label("x".to_string());
+        assert!(label.clone().into_circle().is_err());
+        assert_eq!(label.into_label().ok().as_deref(), Some("x"));
+        assert_eq!(Only::One(1).into_one().ok(), Some(1));
+    }
+}
+
+mod shared_import_return_area {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    name: func() -> string
+                    pair: func() -> tuple<u64, u32>
+                }
+                import root-name: func() -> string
+                export root-export: func() -> string
+            }
+        ",
+        shared_import_return_area,
+    });
+
+    struct Component;
+
+    export_baz!(Component);
+
+    impl Baz for Component {
+        fn root_export() -> String {
+            let (a, b) = imports::pair();
+            format!("{} {} {a} {b}", imports::name(), root_name())
+        }
+    }
+}
+
+mod enum_name_str {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    enum log-level {
+                        debug,
+                        warn-once,
+                    }
+                    log: func(level: log-level)
+                }
+            }
+        ",
+        enum_name_str,
+    });
+
+    #[test]
+    fn names() {
+        use imports::LogLevel;
+
+        assert_eq!(LogLevel::Debug.as_wit_str(), "debug");
+        assert_eq!(<&str>::from(LogLevel::WarnOnce), "warn-once");
+    }
+}
+
+mod types_only {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            interface shared {
+                record point {
+                    x: u32,
+                    y: u32,
+                }
+                enum color {
+                    red,
+                    green,
+                }
+            }
+
+            default world baz {
+                import imports: self.shared
+                export exports: self.shared
+                type id = u64
+            }
+        ",
+    });
+
+    #[test]
+    fn types() {
+        let p = exports::Point { x: 1, y: 2 };
+        assert_eq!(p.x + p.y, 3);
+        let _ = imports::Color::Green;
+        let _: Id = 1;
+    }
+}
+
+mod emit_name_consts {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world the-world {
+                import the-imports: interface {
+                    foo: func()
+                }
+                export the-exports: interface {
+                    bar: func()
+                }
+            }
+        ",
+        emit_name_consts,
+    });
+
+    #[test]
+    fn names() {
+        assert_eq!(WORLD_NAME, "the-world");
+        assert_eq!(the_imports::INTERFACE_NAME, "the-imports");
+        assert_eq!(the_exports::INTERFACE_NAME, "the-exports");
+    }
+}
+
+mod inline_list_capacity {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    record entry {
+                        key: string,
+                        value: u64,
+                    }
+                    put: func(entries: list<entry>, tags: list<list<string>>)
+                }
+            }
+        ",
+        inline_list_capacity: 8,
+    });
+}
+
+mod borrowed_export_params {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                export exports: interface {
+                    record entry {
+                        key: string,
+                        value: u32,
+                    }
+                    join: func(a: list<string>, sep: string) -> string
+                    total: func(entries: list<entry>, scale: u32) -> u32
+                    key: func(e: entry) -> string
+                }
+            }
+        ",
+        borrowed_export_params,
+    });
+
+    struct Component;
+
+    export_baz!(Component);
+
+    impl exports::Exports for Component {
+        fn join(a: &[String], sep: &str) -> String {
+            a.join(sep)
+        }
+
+        fn total(entries: &[exports::Entry], scale: u32) -> u32 {
+            entries.iter().map(|e| e.value * scale).sum()
+        }
+
+        fn key(e: &exports::Entry) -> String {
+            e.key.clone()
+        }
+    }
+}
+
+mod generated_header {
+    wit_bindgen_guest_rust::generate!({
+        inline: "
+            default world baz {
+                import imports: interface {
+                    foo: func(x: string) -> u32
+                }
+            }
+        ",
+        generated_header: "// This file is generated.\n// Do not edit it by hand.",
+    });
+
+    #[allow(dead_code)]
+    fn test() {
+        let _: u32 = imports::foo("x");
+    }
+}
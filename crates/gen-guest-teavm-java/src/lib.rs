@@ -81,9 +81,10 @@ impl TeaVmJava {
 }
 
 impl WorldGenerator for TeaVmJava {
-    fn preprocess(&mut self, resolve: &Resolve, name: &str) {
-        self.name = name.to_string();
+    fn preprocess(&mut self, resolve: &Resolve, world: WorldId) -> anyhow::Result<()> {
+        self.name = resolve.worlds[world].name.clone();
         self.sizes.fill(resolve);
+        Ok(())
     }
 
     fn import_interface(
@@ -173,7 +174,7 @@ impl WorldGenerator for TeaVmJava {
         gen.add_world_fragment();
     }
 
-    fn finish(&mut self, resolve: &Resolve, id: WorldId, files: &mut Files) {
+    fn finish(&mut self, resolve: &Resolve, id: WorldId, files: &mut Files) -> anyhow::Result<()> {
         let world = &resolve.worlds[id];
         let package = format!("wit_{}", world.name.to_snake_case());
         let name = world.name.to_upper_camel_case();
@@ -202,8 +203,7 @@ impl WorldGenerator for TeaVmJava {
         );
 
         let component_type =
-            wit_component::metadata::encode(resolve, id, wit_component::StringEncoding::UTF8)
-                .unwrap()
+            wit_component::metadata::encode(resolve, id, wit_component::StringEncoding::UTF8)?
                 .into_iter()
                 .map(|byte| format!("{byte:02x}"))
                 .collect::<Vec<_>>()
@@ -390,6 +390,8 @@ impl WorldGenerator for TeaVmJava {
                 generate_stub(format!("{name}Impl"), fragments, files);
             }
         }
+
+        Ok(())
     }
 }
 
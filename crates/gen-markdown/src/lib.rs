@@ -30,8 +30,9 @@ impl Opts {
 }
 
 impl WorldGenerator for Markdown {
-    fn preprocess(&mut self, resolve: &Resolve, _name: &str) {
+    fn preprocess(&mut self, resolve: &Resolve, _world: WorldId) -> anyhow::Result<()> {
         self.sizes.fill(resolve);
+        Ok(())
     }
 
     fn import_interface(
@@ -105,7 +106,12 @@ impl WorldGenerator for Markdown {
         }
     }
 
-    fn finish(&mut self, resolve: &Resolve, world: WorldId, files: &mut Files) {
+    fn finish(
+        &mut self,
+        resolve: &Resolve,
+        world: WorldId,
+        files: &mut Files,
+    ) -> anyhow::Result<()> {
         let world = &resolve.worlds[world];
         let parser = Parser::new(&self.src);
         let mut events = Vec::new();
@@ -126,6 +132,7 @@ impl WorldGenerator for Markdown {
 
         files.push(&format!("{}.md", world.name), self.src.as_bytes());
         files.push(&format!("{}.html", world.name), html_output.as_bytes());
+        Ok(())
     }
 }
 
@@ -21,11 +21,91 @@ pub trait RustGenerator<'a> {
         true
     }
 
+    /// Return true iff `std`-only impls should be emitted behind
+    /// `#[cfg(feature = "std")]` instead of depending on `use_std`.
+    fn std_feature(&self) -> bool {
+        false
+    }
+
     /// Return true iff the generator should use `&[u8]` instead of `&str` in bindings.
     fn use_raw_strings(&self) -> bool {
         false
     }
 
+    /// Return true iff records wrapping a single list field should implement
+    /// `IntoIterator` by delegating to that list.
+    fn impl_into_iterator(&self) -> bool {
+        false
+    }
+
+    /// Return true iff enums used as errors should get a `to_errno` method
+    /// mapping each case to a negative C-style error code.
+    fn c_error_codes(&self) -> bool {
+        false
+    }
+
+    /// Return true iff records, variants and unions should derive
+    /// `PartialEq`, and `Eq` when they don't contain floats.
+    fn derive_eq(&self) -> bool {
+        false
+    }
+
+    /// Return true iff items whose Rust name differs from their WIT name
+    /// should get a `#[doc(alias)]` with the WIT name.
+    fn doc_aliases(&self) -> bool {
+        false
+    }
+
+    /// The Rust type used for a `result` without any payloads instead of
+    /// `Result<(), ()>`, if any.
+    fn unit_result_type(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Return true iff type names keep their WIT casing rather than being
+    /// camel-cased, as implemented by `type_name`.
+    fn preserve_wit_case(&self) -> bool {
+        false
+    }
+
+    /// Return true iff `ty`, the payload of a case of the variant `id`, is
+    /// stored in a `Box`.
+    fn box_payload(&self, id: TypeId, ty: &Type) -> bool {
+        drop((id, ty));
+        false
+    }
+
+    /// The alignment of the record `id` if it's overridden to be more than
+    /// that of the canonical ABI.
+    fn record_align(&self, id: TypeId) -> Option<usize> {
+        drop(id);
+        None
+    }
+
+    /// Return true iff the owned form of named `list` types should be a
+    /// newtype wrapping a `Vec` rather than a type alias.
+    fn newtype_list_aliases(&self) -> bool {
+        false
+    }
+
+    /// Return true iff variants should get `as_*`, `into_*` and `is_*`
+    /// accessors for their cases.
+    fn variant_accessors(&self) -> bool {
+        false
+    }
+
+    /// Attribute macros, without the surrounding `#[...]`, applied to every
+    /// record, variant, union, enum and flags type.
+    fn type_attribute_macros(&self) -> &[String] {
+        &[]
+    }
+
+    /// Converts the WIT name of a type or interface into the name used for
+    /// the corresponding Rust type or trait.
+    fn type_name(&self, name: &str) -> String {
+        name.to_upper_camel_case()
+    }
+
     fn push_str(&mut self, s: &str);
     fn info(&self, ty: TypeId) -> TypeInfo;
     fn types_mut(&mut self) -> &mut Types;
@@ -45,6 +125,38 @@ pub trait RustGenerator<'a> {
         }
     }
 
+    /// Prints `#[doc(alias = "...")]` with `wit_name` if `doc_aliases` is
+    /// enabled and it differs from `rust_name`.
+    fn print_doc_alias_name(&mut self, rust_name: &str, wit_name: &str) {
+        if self.doc_aliases() && rust_name != wit_name {
+            self.push_str(&format!("#[doc(alias = \"{wit_name}\")]\n"));
+        }
+    }
+
+    /// Prints the `type_attribute_macros`, which go before any derives.
+    fn print_type_attribute_macros(&mut self) {
+        for attr in self.type_attribute_macros().to_vec() {
+            self.push_str(&format!("#[{attr}]\n"));
+        }
+    }
+
+    /// Prints the attributes shared by every Rust type generated for the
+    /// type `id` as `rust_name`.
+    fn print_type_attrs(&mut self, id: TypeId, rust_name: &str) {
+        self.print_doc_alias(id, rust_name);
+        if self.preserve_wit_case() {
+            self.push_str("#[allow(non_camel_case_types)]\n");
+        }
+    }
+
+    /// Like `print_doc_alias_name`, using the WIT name of the type `id`.
+    fn print_doc_alias(&mut self, id: TypeId, rust_name: &str) {
+        if let Some(wit_name) = &self.resolve().types[id].name {
+            let wit_name = wit_name.clone();
+            self.print_doc_alias_name(rust_name, &wit_name);
+        }
+    }
+
     fn rustdoc_params(&mut self, docs: &[(String, Type)], header: &str) {
         drop((docs, header));
         // let docs = docs
@@ -103,6 +215,12 @@ pub trait RustGenerator<'a> {
         self.rustdoc_params(&func.params, "Parameters");
         // TODO: re-add this when docs are back
         // self.rustdoc_params(&func.results, "Return");
+        let func_name = if sig.use_item_name {
+            func.item_name()
+        } else {
+            &func.name
+        };
+        self.print_doc_alias_name(&to_rust_ident(func_name), func_name);
 
         if !sig.private {
             self.push_str("pub ");
@@ -114,12 +232,7 @@ pub trait RustGenerator<'a> {
             self.push_str("async ");
         }
         self.push_str("fn ");
-        let func_name = if sig.use_item_name {
-            func.item_name()
-        } else {
-            &func.name
-        };
-        self.push_str(&to_rust_ident(&func_name));
+        self.push_str(&to_rust_ident(func_name));
         if let Some(generics) = &sig.generics {
             self.push_str(generics);
         }
@@ -138,13 +251,66 @@ pub trait RustGenerator<'a> {
             self.push_str(&name);
             params.push(name);
             self.push_str(": ");
-            self.print_ty(param, param_mode);
+            self.print_param_ty(param, param_mode, sig);
             self.push_str(",");
         }
         self.push_str(")");
         params
     }
 
+    /// Prints the type of a parameter of a function with the signature `sig`,
+    /// which is `impl IntoIterator` of the element type for anonymous lists
+    /// with `iter_list_params`, and a reference for types owning data with
+    /// `borrowed_params`.
+    fn print_param_ty(&mut self, ty: &Type, mode: TypeMode, sig: &FnSig) {
+        let element = self.list_element(ty);
+        if let (Some(element), true) = (element, sig.iter_list_params) {
+            self.push_str("impl IntoIterator<Item = ");
+            self.print_ty(&element, mode);
+            self.push_str(">");
+        } else if sig.borrowed_params && self.is_borrowed_param(ty) {
+            match (ty, element) {
+                (Type::String, _) => self.print_borrowed_str("'_"),
+                (_, Some(element)) => {
+                    self.push_str("&[");
+                    self.print_ty(&element, mode);
+                    self.push_str("]");
+                }
+                _ => {
+                    self.push_str("&");
+                    self.print_ty(ty, mode);
+                }
+            }
+        } else {
+            self.print_ty(ty, mode);
+        }
+    }
+
+    /// Returns whether `ty` owns data, and so is passed by reference to
+    /// functions with `FnSig::borrowed_params`.
+    fn is_borrowed_param(&self, ty: &Type) -> bool {
+        match ty {
+            Type::String => true,
+            Type::Id(id) => self.info(*id).owns_data(),
+            _ => false,
+        }
+    }
+
+    /// Returns the element type of `ty` if it's an anonymous `list<T>`.
+    fn list_element(&self, ty: &Type) -> Option<Type> {
+        match ty {
+            Type::Id(id) => match &self.resolve().types[*id] {
+                TypeDef {
+                    name: None,
+                    kind: TypeDefKind::List(element),
+                    ..
+                } => Some(*element),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     fn print_result_params(&mut self, results: &Results, mode: TypeMode) {
         match results.len() {
             0 => self.push_str("()"),
@@ -256,6 +422,13 @@ pub trait RustGenerator<'a> {
                 self.push_str(">");
             }
 
+            TypeDefKind::Result(r) if r.ok.is_none() && r.err.is_none() => {
+                match self.unit_result_type() {
+                    Some(ty) => self.push_str(ty),
+                    None => self.push_str("Result<(),()>"),
+                }
+            }
+
             TypeDefKind::Result(r) => {
                 self.push_str("Result<");
                 self.print_optional_ty(r.ok.as_ref(), mode);
@@ -373,6 +546,11 @@ pub trait RustGenerator<'a> {
         if info.result && (!info.param || self.uses_two_names(&info)) {
             result.push((self.result_name(ty), TypeMode::Owned));
         }
+        // Types which no function uses are still defined, in their owned
+        // form, so that interfaces and worlds of only types are usable.
+        if !info.param && !info.result {
+            result.push((self.result_name(ty), TypeMode::Owned));
+        }
         return result;
     }
 
@@ -395,7 +573,7 @@ pub trait RustGenerator<'a> {
             Type::Id(id) => {
                 let ty = &self.resolve().types[*id];
                 match &ty.name {
-                    Some(name) => out.push_str(&name.to_upper_camel_case()),
+                    Some(name) => out.push_str(&self.type_name(name)),
                     None => match &ty.kind {
                         TypeDefKind::Option(ty) => {
                             out.push_str("Optional");
@@ -498,6 +676,8 @@ pub trait RustGenerator<'a> {
         for (name, mode) in self.modes_of(id) {
             let lt = self.lifetime_for(&info, mode);
             self.rustdoc(docs);
+            self.print_type_attrs(id, &name);
+            self.print_type_attribute_macros();
 
             if derive_component {
                 self.push_str("#[derive(wasmtime::component::ComponentType)]\n");
@@ -508,12 +688,17 @@ pub trait RustGenerator<'a> {
                 self.push_str("#[component(record)]\n");
             }
 
-            if !info.owns_data() {
-                self.push_str("#[repr(C)]\n");
+            match self.record_align(id) {
+                Some(align) => self.push_str(&format!("#[repr(C, align({align}))]\n")),
+                None if !info.owns_data() => self.push_str("#[repr(C)]\n"),
+                None => {}
+            }
+            if !info.owns_data() && !self.has_boxed_payload(&Type::Id(id)) {
                 self.push_str("#[derive(Copy, Clone)]\n");
             } else {
                 self.push_str("#[derive(Clone)]\n");
             }
+            self.print_eq_derives(id);
             self.push_str(&format!("pub struct {}", name));
             self.print_generics(lt);
             self.push_str(" {\n");
@@ -551,6 +736,10 @@ pub trait RustGenerator<'a> {
             self.push_str("}\n");
             self.push_str("}\n");
 
+            if self.impl_into_iterator() && mode == TypeMode::Owned {
+                self.print_record_into_iterator(&name, record);
+            }
+
             if info.error {
                 self.push_str("impl");
                 self.print_generics(lt);
@@ -564,20 +753,127 @@ pub trait RustGenerator<'a> {
                 self.push_str("write!(f, \"{:?}\", self)\n");
                 self.push_str("}\n");
                 self.push_str("}\n");
-                if self.use_std() {
-                    self.push_str("impl std::error::Error for ");
-                    self.push_str(&name);
-                    self.push_str("{}\n");
-                }
+                self.print_std_error_impl(&name, lt);
             }
         }
     }
 
+    fn print_record_into_iterator(&mut self, name: &str, record: &Record) {
+        let (field, element) = match &record.fields[..] {
+            [field] => match &field.ty {
+                Type::Id(id) => match &self.resolve().types[*id].kind {
+                    TypeDefKind::List(element) => (to_rust_ident(&field.name), *element),
+                    _ => return,
+                },
+                _ => return,
+            },
+            _ => return,
+        };
+
+        self.push_str(&format!("impl IntoIterator for {name} {{\n"));
+        self.push_str("type Item = ");
+        self.print_ty(&element, TypeMode::Owned);
+        self.push_str(";\n");
+        self.push_str("type IntoIter = <Vec<Self::Item> as IntoIterator>::IntoIter;\n");
+        self.push_str("fn into_iter(self) -> Self::IntoIter {\n");
+        self.push_str(&format!("self.{field}.into_iter()\n"));
+        self.push_str("}\n");
+        self.push_str("}\n");
+
+        self.push_str(&format!("impl<'a> IntoIterator for &'a {name} {{\n"));
+        self.push_str("type Item = &'a ");
+        self.print_ty(&element, TypeMode::Owned);
+        self.push_str(";\n");
+        self.push_str("type IntoIter = core::slice::Iter<'a, ");
+        self.print_ty(&element, TypeMode::Owned);
+        self.push_str(">;\n");
+        self.push_str("fn into_iter(self) -> Self::IntoIter {\n");
+        self.push_str(&format!("self.{field}.iter()\n"));
+        self.push_str("}\n");
+        self.push_str("}\n");
+    }
+
+    /// Prints `#[derive(PartialEq, Eq)]` for `id` if `derive_eq` is enabled,
+    /// leaving out `Eq` if the type contains floats.
+    fn print_eq_derives(&mut self, id: TypeId) {
+        if !self.derive_eq() {
+            return;
+        }
+        if self.has_float(&Type::Id(id)) {
+            self.push_str("#[derive(PartialEq)]\n");
+        } else {
+            self.push_str("#[derive(PartialEq, Eq)]\n");
+        }
+    }
+
+    /// Returns whether `ty` transitively contains a variant payload stored in
+    /// a `Box`, which keeps it from being `Copy`.
+    fn has_boxed_payload(&self, ty: &Type) -> bool {
+        let id = match ty {
+            Type::Id(id) => *id,
+            _ => return false,
+        };
+        match &self.resolve().types[id].kind {
+            TypeDefKind::Record(r) => r.fields.iter().any(|f| self.has_boxed_payload(&f.ty)),
+            TypeDefKind::Tuple(t) => t.types.iter().any(|t| self.has_boxed_payload(t)),
+            TypeDefKind::Variant(v) => v.cases.iter().any(|c| {
+                c.ty.as_ref().map_or(false, |t| {
+                    self.box_payload(id, t) || self.has_boxed_payload(t)
+                })
+            }),
+            TypeDefKind::Union(u) => u.cases.iter().any(|c| self.has_boxed_payload(&c.ty)),
+            TypeDefKind::Result(r) => {
+                r.ok.iter()
+                    .chain(r.err.iter())
+                    .any(|t| self.has_boxed_payload(t))
+            }
+            TypeDefKind::Option(t) | TypeDefKind::Type(t) => self.has_boxed_payload(t),
+            // These are never `Copy` to begin with, or never contain variants.
+            TypeDefKind::List(_)
+            | TypeDefKind::Future(_)
+            | TypeDefKind::Stream(_)
+            | TypeDefKind::Flags(_)
+            | TypeDefKind::Enum(_) => false,
+            TypeDefKind::Unknown => unreachable!(),
+        }
+    }
+
+    /// Returns whether `ty` transitively contains a `float32` or `float64`.
+    fn has_float(&self, ty: &Type) -> bool {
+        let id = match ty {
+            Type::Float32 | Type::Float64 => return true,
+            Type::Id(id) => *id,
+            _ => return false,
+        };
+        match &self.resolve().types[id].kind {
+            TypeDefKind::Record(r) => r.fields.iter().any(|f| self.has_float(&f.ty)),
+            TypeDefKind::Tuple(t) => t.types.iter().any(|t| self.has_float(t)),
+            TypeDefKind::Variant(v) => v
+                .cases
+                .iter()
+                .any(|c| c.ty.as_ref().map_or(false, |t| self.has_float(t))),
+            TypeDefKind::Union(u) => u.cases.iter().any(|c| self.has_float(&c.ty)),
+            TypeDefKind::Result(r) => r.ok.iter().chain(r.err.iter()).any(|t| self.has_float(t)),
+            TypeDefKind::Option(t) | TypeDefKind::List(t) | TypeDefKind::Type(t) => {
+                self.has_float(t)
+            }
+            TypeDefKind::Future(t) => t.as_ref().map_or(false, |t| self.has_float(t)),
+            TypeDefKind::Stream(s) => s
+                .element
+                .iter()
+                .chain(s.end.iter())
+                .any(|t| self.has_float(t)),
+            TypeDefKind::Flags(_) | TypeDefKind::Enum(_) => false,
+            TypeDefKind::Unknown => unreachable!(),
+        }
+    }
+
     fn print_typedef_tuple(&mut self, id: TypeId, tuple: &Tuple, docs: &Docs) {
         let info = self.info(id);
         for (name, mode) in self.modes_of(id) {
             let lt = self.lifetime_for(&info, mode);
             self.rustdoc(docs);
+            self.print_type_attrs(id, &name);
             self.push_str(&format!("pub type {}", name));
             self.print_generics(lt);
             self.push_str(" = (");
@@ -615,6 +911,80 @@ pub trait RustGenerator<'a> {
                 None
             },
         );
+        if self.variant_accessors() {
+            for (name, mode) in self.modes_of(id) {
+                self.print_variant_accessors(id, mode, &name, variant);
+            }
+        }
+    }
+
+    /// Prints `as_*` and `into_*` accessors for the cases of `variant` with a
+    /// payload, and `is_*` for those without.
+    fn print_variant_accessors(
+        &mut self,
+        id: TypeId,
+        mode: TypeMode,
+        name: &str,
+        variant: &Variant,
+    ) {
+        let info = self.info(id);
+        let lt = self.lifetime_for(&info, mode);
+        // With a single case the catch-all arms below are unreachable.
+        self.push_str("#[allow(unreachable_patterns)]\n");
+        self.push_str("impl");
+        self.print_generics(lt);
+        self.push_str(&format!(" {name}"));
+        self.print_generics(lt);
+        self.push_str(" {\n");
+        for case in variant.cases.iter() {
+            let case_name = case.name.to_upper_camel_case();
+            let snake = case.name.to_snake_case();
+            let ty = match &case.ty {
+                Some(ty) => ty,
+                None => {
+                    self.push_str(&format!(
+                        "/// Returns whether this is the `{}` case.\n",
+                        case.name
+                    ));
+                    self.push_str(&format!(
+                        "pub fn is_{snake}(&self) -> bool {{ matches!(self, Self::{case_name}) }}\n"
+                    ));
+                    continue;
+                }
+            };
+            let boxed = self.box_payload(id, ty);
+            self.push_str(&format!(
+                "/// Returns the payload of the `{}` case, if this is one.\n",
+                case.name
+            ));
+            self.push_str(&format!("pub fn as_{snake}(&self) -> Option<&"));
+            self.print_ty(ty, mode);
+            let payload = if boxed { "&**e" } else { "e" };
+            self.push_str(&format!(
+                "> {{
+                    match self {{
+                        Self::{case_name}(e) => Some({payload}),
+                        _ => None,
+                    }}
+                }}\n"
+            ));
+            self.push_str(&format!(
+                "/// Returns the payload of the `{}` case, or `self` if this is another case.\n",
+                case.name
+            ));
+            self.push_str(&format!("pub fn into_{snake}(self) -> Result<"));
+            self.print_ty(ty, mode);
+            let payload = if boxed { "*e" } else { "e" };
+            self.push_str(&format!(
+                ", Self> {{
+                    match self {{
+                        Self::{case_name}(e) => Ok({payload}),
+                        other => Err(other),
+                    }}
+                }}\n"
+            ));
+        }
+        self.push_str("}\n");
     }
 
     fn print_typedef_union(
@@ -651,9 +1021,9 @@ pub trait RustGenerator<'a> {
         let info = self.info(id);
 
         for (name, mode) in self.modes_of(id) {
-            let name = name.to_upper_camel_case();
-
             self.rustdoc(docs);
+            self.print_type_attrs(id, &name);
+            self.print_type_attribute_macros();
             let lt = self.lifetime_for(&info, mode);
             if let Some(derive_component) = derive_component {
                 self.push_str("#[derive(wasmtime::component::ComponentType)]\n");
@@ -663,11 +1033,22 @@ pub trait RustGenerator<'a> {
                 self.push_str("#[derive(wasmtime::component::Lower)]\n");
                 self.push_str(&format!("#[component({})]\n", derive_component));
             }
-            if !info.owns_data() {
+            if !info.owns_data() && !self.has_boxed_payload(&Type::Id(id)) {
                 self.push_str("#[derive(Clone, Copy)]\n");
             } else {
                 self.push_str("#[derive(Clone)]\n");
             }
+            if cases
+                .clone()
+                .into_iter()
+                .all(|(_, _, _, payload)| payload.is_none())
+            {
+                // Variants without payloads are as cheap to compare and hash
+                // as enums, so they're usable as map keys in the same way.
+                self.push_str("#[derive(PartialEq, Eq, Hash)]\n");
+            } else {
+                self.print_eq_derives(id);
+            }
             self.push_str(&format!("pub enum {name}"));
             self.print_generics(lt);
             self.push_str("{\n");
@@ -681,7 +1062,13 @@ pub trait RustGenerator<'a> {
                 self.push_str(&case_name);
                 if let Some(ty) = payload {
                     self.push_str("(");
-                    self.print_ty(ty, mode);
+                    if self.box_payload(id, ty) {
+                        self.push_str("Box<");
+                        self.print_ty(ty, mode);
+                        self.push_str(">");
+                    } else {
+                        self.print_ty(ty, mode);
+                    }
                     self.push_str(")")
                 }
                 self.push_str(",\n");
@@ -713,18 +1100,27 @@ pub trait RustGenerator<'a> {
                 self.push_str("}\n");
                 self.push_str("\n");
 
-                if self.use_std() {
-                    self.push_str("impl");
-                    self.print_generics(lt);
-                    self.push_str(" std::error::Error for ");
-                    self.push_str(&name);
-                    self.print_generics(lt);
-                    self.push_str(" {}\n");
-                }
+                self.print_std_error_impl(&name, lt);
             }
         }
     }
 
+    /// Prints `impl std::error::Error` for `name`, gated on the `std` feature
+    /// if `std_feature` is set and omitted entirely if `std` can't be used.
+    fn print_std_error_impl(&mut self, name: &str, lt: Option<&str>) {
+        if self.std_feature() {
+            self.push_str("#[cfg(feature = \"std\")]\n");
+        } else if !self.use_std() {
+            return;
+        }
+        self.push_str("impl");
+        self.print_generics(lt);
+        self.push_str(" std::error::Error for ");
+        self.push_str(name);
+        self.print_generics(lt);
+        self.push_str(" {}\n");
+    }
+
     fn print_rust_enum_debug<'b>(
         &mut self,
         id: TypeId,
@@ -769,6 +1165,7 @@ pub trait RustGenerator<'a> {
 
         for (name, mode) in self.modes_of(id) {
             self.rustdoc(docs);
+            self.print_type_attrs(id, &name);
             let lt = self.lifetime_for(&info, mode);
             self.push_str(&format!("pub type {}", name));
             self.print_generics(lt);
@@ -783,9 +1180,16 @@ pub trait RustGenerator<'a> {
 
         for (name, mode) in self.modes_of(id) {
             self.rustdoc(docs);
+            self.print_type_attrs(id, &name);
             let lt = self.lifetime_for(&info, mode);
             self.push_str(&format!("pub type {}", name));
             self.print_generics(lt);
+            if result.ok.is_none() && result.err.is_none() {
+                if let Some(ty) = self.unit_result_type() {
+                    self.push_str(&format!("= {ty};\n"));
+                    continue;
+                }
+            }
             self.push_str("= Result<");
             self.print_optional_ty(result.ok.as_ref(), mode);
             self.push_str(",");
@@ -807,15 +1211,17 @@ pub trait RustGenerator<'a> {
     {
         let info = self.info(id);
 
-        let name = name.to_upper_camel_case();
+        let name = self.type_name(name);
         self.rustdoc(docs);
+        self.print_type_attrs(id, &name);
+        self.print_type_attribute_macros();
         for attr in attrs {
             self.push_str(&format!("{}\n", attr));
         }
         self.push_str("#[repr(");
         self.int_repr(enum_.tag());
-        self.push_str(")]\n#[derive(Clone, Copy, PartialEq, Eq)]\n");
-        self.push_str(&format!("pub enum {} {{\n", name.to_upper_camel_case()));
+        self.push_str(")]\n#[derive(Clone, Copy, PartialEq, Eq, Hash)]\n");
+        self.push_str(&format!("pub enum {} {{\n", name));
         for case in enum_.cases.iter() {
             self.rustdoc(&case.docs);
             self.push_str(&case_attr(case));
@@ -859,6 +1265,23 @@ pub trait RustGenerator<'a> {
             self.push_str("}\n");
             self.push_str("}\n");
 
+            if self.c_error_codes() {
+                self.push_str(
+                    "/// Returns a negative C-style error code for this error, where the\n\
+                     /// first case maps to `-1`, the second to `-2`, and so on.\n",
+                );
+                self.push_str("pub fn to_errno(&self) -> i32 {\n");
+                self.push_str("match self {\n");
+                for (i, case) in enum_.cases.iter().enumerate() {
+                    self.push_str(&name);
+                    self.push_str("::");
+                    self.push_str(&case.name.to_upper_camel_case());
+                    self.push_str(&format!(" => -{},\n", i + 1));
+                }
+                self.push_str("}\n");
+                self.push_str("}\n");
+            }
+
             self.push_str("}\n");
 
             self.push_str("impl core::fmt::Debug for ");
@@ -885,11 +1308,7 @@ pub trait RustGenerator<'a> {
             self.push_str("}\n");
             self.push_str("}\n");
             self.push_str("\n");
-            if self.use_std() {
-                self.push_str("impl std::error::Error for ");
-                self.push_str(&name);
-                self.push_str("{}\n");
-            }
+            self.print_std_error_impl(&name, None);
         } else {
             self.print_rust_enum_debug(
                 id,
@@ -907,6 +1326,7 @@ pub trait RustGenerator<'a> {
         let info = self.info(id);
         for (name, mode) in self.modes_of(id) {
             self.rustdoc(docs);
+            self.print_type_attrs(id, &name);
             self.push_str(&format!("pub type {}", name));
             let lt = self.lifetime_for(&info, mode);
             self.print_generics(lt);
@@ -921,6 +1341,11 @@ pub trait RustGenerator<'a> {
         for (name, mode) in self.modes_of(id) {
             let lt = self.lifetime_for(&info, mode);
             self.rustdoc(docs);
+            self.print_type_attrs(id, &name);
+            if self.newtype_list_aliases() && mode == TypeMode::Owned {
+                self.print_list_newtype(id, &name, ty);
+                continue;
+            }
             self.push_str(&format!("pub type {}", name));
             self.print_generics(lt);
             self.push_str(" = ");
@@ -929,13 +1354,54 @@ pub trait RustGenerator<'a> {
         }
     }
 
+    /// Prints the newtype `name` wrapping a `Vec` of `ty` for the named list
+    /// type `id`, along with conversions to and from the `Vec`.
+    fn print_list_newtype(&mut self, id: TypeId, name: &str, ty: &Type) {
+        self.print_type_attribute_macros();
+        self.push_str("#[derive(Clone, Debug, Default)]\n");
+        self.print_eq_derives(id);
+        self.push_str(&format!("pub struct {name}(pub "));
+        self.print_list(ty, TypeMode::Owned);
+        self.push_str(");\n");
+
+        self.push_str(&format!("impl core::ops::Deref for {name} {{\n"));
+        self.push_str("type Target = ");
+        self.print_list(ty, TypeMode::Owned);
+        self.push_str(";\n");
+        self.push_str("fn deref(&self) -> &Self::Target {\n&self.0\n}\n");
+        self.push_str("}\n");
+
+        self.push_str(&format!("impl core::ops::DerefMut for {name} {{\n"));
+        self.push_str("fn deref_mut(&mut self) -> &mut Self::Target {\n&mut self.0\n}\n");
+        self.push_str("}\n");
+
+        self.push_str("impl From<");
+        self.print_list(ty, TypeMode::Owned);
+        self.push_str(&format!("> for {name} {{\n"));
+        self.push_str("fn from(list: ");
+        self.print_list(ty, TypeMode::Owned);
+        self.push_str(") -> Self {\nSelf(list)\n}\n");
+        self.push_str("}\n");
+
+        self.push_str(&format!("impl From<{name}> for "));
+        self.print_list(ty, TypeMode::Owned);
+        self.push_str(" {\n");
+        self.push_str(&format!("fn from(list: {name}) -> Self {{\nlist.0\n}}\n"));
+        self.push_str("}\n");
+
+        self.push_str(&format!("impl IntoIterator for {name} {{\n"));
+        self.push_str("type Item = ");
+        self.print_ty(ty, TypeMode::Owned);
+        self.push_str(";\n");
+        self.push_str("type IntoIter = <Vec<Self::Item> as IntoIterator>::IntoIter;\n");
+        self.push_str("fn into_iter(self) -> Self::IntoIter {\nself.0.into_iter()\n}\n");
+        self.push_str("}\n");
+    }
+
     fn param_name(&self, ty: TypeId) -> String {
         let info = self.info(ty);
-        let name = self.resolve().types[ty]
-            .name
-            .as_ref()
-            .unwrap()
-            .to_upper_camel_case();
+        let name = self.resolve().types[ty].name.as_ref().unwrap();
+        let name = self.type_name(name);
         if self.uses_two_names(&info) {
             format!("{}Param", name)
         } else {
@@ -945,11 +1411,8 @@ pub trait RustGenerator<'a> {
 
     fn result_name(&self, ty: TypeId) -> String {
         let info = self.info(ty);
-        let name = self.resolve().types[ty]
-            .name
-            .as_ref()
-            .unwrap()
-            .to_upper_camel_case();
+        let name = self.resolve().types[ty].name.as_ref().unwrap();
+        let name = self.type_name(name);
         if self.uses_two_names(&info) {
             format!("{}Result", name)
         } else {
@@ -984,6 +1447,8 @@ pub struct FnSig {
     pub generics: Option<String>,
     pub self_arg: Option<String>,
     pub self_is_first_param: bool,
+    pub iter_list_params: bool,
+    pub borrowed_params: bool,
 }
 
 pub trait RustFunctionGenerator {
@@ -4,7 +4,8 @@ use syn::parse::{Error, Parse, ParseStream, Result};
 use syn::punctuated::Punctuated;
 use syn::{token, Token};
 use wit_bindgen_core::wit_parser::{PackageId, Resolve, UnresolvedPackage, WorldId};
-use wit_bindgen_gen_guest_rust::Opts;
+use wit_bindgen_gen_guest_rust::{Opts, Visibility};
+use wit_component::StringEncoding;
 
 #[proc_macro]
 pub fn generate(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
@@ -19,6 +20,7 @@ struct Config {
     resolve: Resolve,
     world: WorldId,
     files: Vec<PathBuf>,
+    debug: bool,
 }
 
 enum Source {
@@ -32,6 +34,7 @@ impl Parse for Config {
         let mut opts = Opts::default();
         let mut world = None;
         let mut source = None;
+        let mut debug = std::env::var("WIT_BINDGEN_DEBUG").map_or(false, |v| v == "1");
 
         let document = if input.peek(token::Brace) {
             let content;
@@ -60,11 +63,86 @@ impl Parse for Config {
                     }
                     Opt::Unchecked => opts.unchecked = true,
                     Opt::NoStd => opts.no_std = true,
+                    Opt::StdFeature => opts.std_feature = true,
                     Opt::RawStrings => opts.raw_strings = true,
                     Opt::MacroExport => opts.macro_export = true,
                     Opt::MacroCallPrefix(prefix) => opts.macro_call_prefix = Some(prefix.value()),
                     Opt::ExportMacroName(name) => opts.export_macro_name = Some(name.value()),
                     Opt::Skip(list) => opts.skip.extend(list.iter().map(|i| i.value())),
+                    Opt::ImplIntoIterator => opts.impl_into_iterator = true,
+                    Opt::CErrorCodes => opts.c_error_codes = true,
+                    Opt::SafeEnumLift => opts.safe_enum_lift = true,
+                    Opt::AllocatorPath(path) => opts.allocator_path = Some(path.value()),
+                    Opt::NativeExportStubs => opts.native_export_stubs = true,
+                    Opt::NativeImportTrap => opts.native_import_trap = true,
+                    Opt::MaxListLen(len) => opts.max_list_len = Some(len.base10_parse()?),
+                    Opt::DeriveEq => opts.derive_eq = true,
+                    Opt::EmitLayoutConsts => opts.emit_layout_consts = true,
+                    Opt::DocAliases => opts.doc_aliases = true,
+                    Opt::ExternAbi(abi) => opts.extern_abi = Some(abi.value()),
+                    Opt::ExposeRawImports => opts.expose_raw_imports = true,
+                    Opt::UnitResultAsStatus => opts.unit_result_as_status = true,
+                    Opt::ListIntoBuffer => opts.list_into_buffer = true,
+                    Opt::WasmOnlyExterns => opts.wasm_only_externs = true,
+                    Opt::EnumStr => opts.enum_str = true,
+                    Opt::ImportModuleAliases(aliases) => opts.import_module_aliases.extend(aliases),
+                    Opt::UnsafeModule => opts.unsafe_module = true,
+                    Opt::CTaggedVariants => opts.c_tagged_variants = true,
+                    Opt::PreserveWitCase => opts.preserve_wit_case = true,
+                    Opt::BoxLargeVariants(max) => {
+                        opts.box_large_variants = Some(max.base10_parse()?)
+                    }
+                    Opt::DocAbiDetails => opts.doc_abi_details = true,
+                    Opt::SplitRawWrappers => opts.split_raw_wrappers = true,
+                    Opt::DebugListBounds => opts.debug_list_bounds = true,
+                    Opt::TypeAttributeMacros(list) => opts
+                        .type_attribute_macros
+                        .extend(list.iter().map(|i| i.value())),
+                    Opt::ExportContextType(ty) => opts.export_context_type = Some(ty.value()),
+                    Opt::RecordAlignOverrides(aligns) => opts.record_align_overrides.extend(aligns),
+                    Opt::NewtypeListAliases => opts.newtype_list_aliases = true,
+                    Opt::TypeNamePrefix(prefix) => opts.type_name_prefix = Some(prefix.value()),
+                    Opt::ColdTrapFns => opts.cold_trap_fns = true,
+                    Opt::AbiVersionConst(name) => opts.abi_version_const = Some(name.value()),
+                    Opt::StringEncoding(encoding) => {
+                        opts.string_encoding = match encoding.value().as_str() {
+                            "utf8" => StringEncoding::UTF8,
+                            "utf16" => StringEncoding::UTF16,
+                            _ => {
+                                return Err(Error::new(
+                                    encoding.span(),
+                                    "expected `utf8` or `utf16`",
+                                ))
+                            }
+                        }
+                    }
+                    Opt::ListParamsImplIntoiterator => opts.list_params_impl_intoiterator = true,
+                    Opt::VariantAccessors => opts.variant_accessors = true,
+                    Opt::SharedImportReturnArea => opts.shared_import_return_area = true,
+                    Opt::EnumNameStr => opts.enum_name_str = true,
+                    Opt::EmitNameConsts => opts.emit_name_consts = true,
+                    Opt::InlineListCapacity(cap) => {
+                        opts.inline_list_capacity = Some(cap.base10_parse()?)
+                    }
+                    Opt::BorrowedExportParams => opts.borrowed_export_params = true,
+                    Opt::GeneratedHeader(h) => opts.generated_header = Some(h.value()),
+                    Opt::Visibility(vis) => {
+                        opts.visibility = match vis.value().as_str() {
+                            "pub" => Visibility::Pub,
+                            "pub(crate)" => Visibility::PubCrate,
+                            "private" => Visibility::Private,
+                            _ => {
+                                return Err(Error::new(
+                                    vis.span(),
+                                    "expected `pub`, `pub(crate)` or `private`",
+                                ))
+                            }
+                        }
+                    }
+                    Opt::Acronyms(list) => opts.acronyms.extend(list.iter().map(|i| i.value())),
+                    Opt::WasmBindgenCompat => opts.wasm_bindgen_compat = true,
+                    Opt::Prelude => opts.prelude = true,
+                    Opt::Debug => debug = true,
                 }
             }
             match (document, &source) {
@@ -109,6 +187,7 @@ impl Parse for Config {
             resolve,
             world,
             files,
+            debug,
         })
     }
 }
@@ -150,15 +229,101 @@ fn parse_doc(s: &str, world: &mut Option<String>) -> String {
     }
 }
 
+/// Makes a best-effort guess at the line of `src` which prevents it from
+/// being tokenized by finding the first mismatched delimiter, or the last
+/// unclosed delimiter or string literal.
+fn unbalanced_line(src: &str) -> Option<usize> {
+    let mut stack = Vec::new();
+    let mut string_start = None;
+    for (line, text) in src.lines().enumerate() {
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if string_start.is_some() {
+                match c {
+                    '\\' => {
+                        chars.next();
+                    }
+                    '"' => string_start = None,
+                    _ => {}
+                }
+                continue;
+            }
+            match c {
+                '"' => string_start = Some(line),
+                '/' if chars.as_str().starts_with('/') => break,
+                '(' | '[' | '{' => stack.push((c, line)),
+                ')' | ']' | '}' => {
+                    let open = match c {
+                        ')' => '(',
+                        ']' => '[',
+                        _ => '{',
+                    };
+                    match stack.pop() {
+                        Some((c, _)) if c == open => {}
+                        _ => return Some(line),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    string_start.or(stack.last().map(|(_, line)| *line))
+}
+
 impl Config {
     fn expand(self) -> Result<TokenStream> {
         let mut files = Default::default();
         self.opts
             .build()
-            .generate(&self.resolve, self.world, &mut files);
+            .generate(&self.resolve, self.world, &mut files)
+            .map_err(|e| Error::new(Span::call_site(), format!("{e:?}")))?;
         let (_, src) = files.iter().next().unwrap();
         let src = std::str::from_utf8(src).unwrap();
-        let mut contents = src.parse::<TokenStream>().unwrap();
+
+        // Write out the generated source, if requested, so it can be inspected
+        // when it fails to compile.
+        let debug_path = if self.debug {
+            let name = &self.resolve.worlds[self.world].name;
+            let dir = std::env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(std::env::temp_dir);
+            let path = dir.join(format!("wit-bindgen-debug-{name}.rs"));
+            std::fs::write(&path, src).map_err(|e| {
+                Error::new(
+                    Span::call_site(),
+                    format!("failed to write `{}`: {e}", path.display()),
+                )
+            })?;
+            Some(path)
+        } else {
+            None
+        };
+
+        let mut contents = src.parse::<TokenStream>().map_err(|e| {
+            let mut msg = format!(
+                "failed to parse generated bindings, this is a bug in \
+                 wit-bindgen: {e}"
+            );
+            if let Some(line) = unbalanced_line(src) {
+                msg.push_str(&format!(
+                    "\nnear line {} of the generated source:\n",
+                    line + 1
+                ));
+                let lines = src.lines().collect::<Vec<_>>();
+                let start = line.saturating_sub(2);
+                let end = (line + 3).min(lines.len());
+                for (i, text) in lines[start..end].iter().enumerate() {
+                    msg.push_str(&format!("{:>5} | {}\n", start + i + 1, text.trim_end()));
+                }
+            }
+            if let Some(path) = &debug_path {
+                msg.push_str(&format!(
+                    "\ngenerated source written to `{}`",
+                    path.display()
+                ));
+            }
+            Error::new(Span::call_site(), msg)
+        })?;
 
         // Include a dummy `include_str!` for any files we read so rustc knows that
         // we depend on the contents of those files.
@@ -177,11 +342,57 @@ impl Config {
 mod kw {
     syn::custom_keyword!(unchecked);
     syn::custom_keyword!(no_std);
+    syn::custom_keyword!(std_feature);
     syn::custom_keyword!(raw_strings);
     syn::custom_keyword!(macro_export);
     syn::custom_keyword!(macro_call_prefix);
     syn::custom_keyword!(export_macro_name);
     syn::custom_keyword!(skip);
+    syn::custom_keyword!(impl_into_iterator);
+    syn::custom_keyword!(c_error_codes);
+    syn::custom_keyword!(safe_enum_lift);
+    syn::custom_keyword!(allocator_path);
+    syn::custom_keyword!(native_export_stubs);
+    syn::custom_keyword!(native_import_trap);
+    syn::custom_keyword!(max_list_len);
+    syn::custom_keyword!(visibility);
+    syn::custom_keyword!(generated_header);
+    syn::custom_keyword!(borrowed_export_params);
+    syn::custom_keyword!(inline_list_capacity);
+    syn::custom_keyword!(emit_name_consts);
+    syn::custom_keyword!(enum_name_str);
+    syn::custom_keyword!(shared_import_return_area);
+    syn::custom_keyword!(variant_accessors);
+    syn::custom_keyword!(list_params_impl_intoiterator);
+    syn::custom_keyword!(string_encoding);
+    syn::custom_keyword!(abi_version_const);
+    syn::custom_keyword!(cold_trap_fns);
+    syn::custom_keyword!(type_name_prefix);
+    syn::custom_keyword!(newtype_list_aliases);
+    syn::custom_keyword!(record_align_overrides);
+    syn::custom_keyword!(export_context_type);
+    syn::custom_keyword!(type_attribute_macros);
+    syn::custom_keyword!(debug_list_bounds);
+    syn::custom_keyword!(split_raw_wrappers);
+    syn::custom_keyword!(doc_abi_details);
+    syn::custom_keyword!(box_large_variants);
+    syn::custom_keyword!(preserve_wit_case);
+    syn::custom_keyword!(c_tagged_variants);
+    syn::custom_keyword!(unsafe_module);
+    syn::custom_keyword!(import_module_aliases);
+    syn::custom_keyword!(enum_str);
+    syn::custom_keyword!(wasm_only_externs);
+    syn::custom_keyword!(list_into_buffer);
+    syn::custom_keyword!(unit_result_as_status);
+    syn::custom_keyword!(expose_raw_imports);
+    syn::custom_keyword!(extern_abi);
+    syn::custom_keyword!(doc_aliases);
+    syn::custom_keyword!(emit_layout_consts);
+    syn::custom_keyword!(derive_eq);
+    syn::custom_keyword!(acronyms);
+    syn::custom_keyword!(wasm_bindgen_compat);
+    syn::custom_keyword!(prelude);
+    syn::custom_keyword!(debug);
     syn::custom_keyword!(world);
     syn::custom_keyword!(path);
     syn::custom_keyword!(inline);
@@ -193,11 +404,57 @@ enum Opt {
     Inline(syn::LitStr),
     Unchecked,
     NoStd,
+    StdFeature,
     RawStrings,
     MacroExport,
     MacroCallPrefix(syn::LitStr),
     ExportMacroName(syn::LitStr),
     Skip(Vec<syn::LitStr>),
+    ImplIntoIterator,
+    CErrorCodes,
+    SafeEnumLift,
+    AllocatorPath(syn::LitStr),
+    NativeExportStubs,
+    NativeImportTrap,
+    MaxListLen(syn::LitInt),
+    Visibility(syn::LitStr),
+    GeneratedHeader(syn::LitStr),
+    BorrowedExportParams,
+    InlineListCapacity(syn::LitInt),
+    EmitNameConsts,
+    EnumNameStr,
+    SharedImportReturnArea,
+    VariantAccessors,
+    ListParamsImplIntoiterator,
+    StringEncoding(syn::LitStr),
+    AbiVersionConst(syn::LitStr),
+    ColdTrapFns,
+    TypeNamePrefix(syn::LitStr),
+    NewtypeListAliases,
+    RecordAlignOverrides(Vec<(String, usize)>),
+    ExportContextType(syn::LitStr),
+    TypeAttributeMacros(Vec<syn::LitStr>),
+    DebugListBounds,
+    SplitRawWrappers,
+    DocAbiDetails,
+    BoxLargeVariants(syn::LitInt),
+    PreserveWitCase,
+    CTaggedVariants,
+    UnsafeModule,
+    ImportModuleAliases(Vec<(String, String)>),
+    EnumStr,
+    WasmOnlyExterns,
+    ListIntoBuffer,
+    UnitResultAsStatus,
+    ExposeRawImports,
+    ExternAbi(syn::LitStr),
+    DocAliases,
+    EmitLayoutConsts,
+    DeriveEq,
+    Acronyms(Vec<syn::LitStr>),
+    WasmBindgenCompat,
+    Prelude,
+    Debug,
 }
 
 impl Parse for Opt {
@@ -221,6 +478,9 @@ impl Parse for Opt {
         } else if l.peek(kw::no_std) {
             input.parse::<kw::no_std>()?;
             Ok(Opt::NoStd)
+        } else if l.peek(kw::std_feature) {
+            input.parse::<kw::std_feature>()?;
+            Ok(Opt::StdFeature)
         } else if l.peek(kw::raw_strings) {
             input.parse::<kw::raw_strings>()?;
             Ok(Opt::RawStrings)
@@ -242,6 +502,188 @@ impl Parse for Opt {
             syn::bracketed!(contents in input);
             let list = Punctuated::<_, Token![,]>::parse_terminated(&contents)?;
             Ok(Opt::Skip(list.iter().cloned().collect()))
+        } else if l.peek(kw::impl_into_iterator) {
+            input.parse::<kw::impl_into_iterator>()?;
+            Ok(Opt::ImplIntoIterator)
+        } else if l.peek(kw::c_error_codes) {
+            input.parse::<kw::c_error_codes>()?;
+            Ok(Opt::CErrorCodes)
+        } else if l.peek(kw::safe_enum_lift) {
+            input.parse::<kw::safe_enum_lift>()?;
+            Ok(Opt::SafeEnumLift)
+        } else if l.peek(kw::allocator_path) {
+            input.parse::<kw::allocator_path>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::AllocatorPath(input.parse()?))
+        } else if l.peek(kw::native_export_stubs) {
+            input.parse::<kw::native_export_stubs>()?;
+            Ok(Opt::NativeExportStubs)
+        } else if l.peek(kw::native_import_trap) {
+            input.parse::<kw::native_import_trap>()?;
+            Ok(Opt::NativeImportTrap)
+        } else if l.peek(kw::max_list_len) {
+            input.parse::<kw::max_list_len>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::MaxListLen(input.parse()?))
+        } else if l.peek(kw::visibility) {
+            input.parse::<kw::visibility>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::Visibility(input.parse()?))
+        } else if l.peek(kw::derive_eq) {
+            input.parse::<kw::derive_eq>()?;
+            Ok(Opt::DeriveEq)
+        } else if l.peek(kw::acronyms) {
+            input.parse::<kw::acronyms>()?;
+            input.parse::<Token![:]>()?;
+            let contents;
+            syn::bracketed!(contents in input);
+            let list = Punctuated::<_, Token![,]>::parse_terminated(&contents)?;
+            Ok(Opt::Acronyms(list.iter().cloned().collect()))
+        } else if l.peek(kw::wasm_bindgen_compat) {
+            input.parse::<kw::wasm_bindgen_compat>()?;
+            Ok(Opt::WasmBindgenCompat)
+        } else if l.peek(kw::prelude) {
+            input.parse::<kw::prelude>()?;
+            Ok(Opt::Prelude)
+        } else if l.peek(kw::debug) {
+            input.parse::<kw::debug>()?;
+            Ok(Opt::Debug)
+        } else if l.peek(kw::emit_layout_consts) {
+            input.parse::<kw::emit_layout_consts>()?;
+            Ok(Opt::EmitLayoutConsts)
+        } else if l.peek(kw::doc_aliases) {
+            input.parse::<kw::doc_aliases>()?;
+            Ok(Opt::DocAliases)
+        } else if l.peek(kw::extern_abi) {
+            input.parse::<kw::extern_abi>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::ExternAbi(input.parse()?))
+        } else if l.peek(kw::expose_raw_imports) {
+            input.parse::<kw::expose_raw_imports>()?;
+            Ok(Opt::ExposeRawImports)
+        } else if l.peek(kw::unit_result_as_status) {
+            input.parse::<kw::unit_result_as_status>()?;
+            Ok(Opt::UnitResultAsStatus)
+        } else if l.peek(kw::list_into_buffer) {
+            input.parse::<kw::list_into_buffer>()?;
+            Ok(Opt::ListIntoBuffer)
+        } else if l.peek(kw::wasm_only_externs) {
+            input.parse::<kw::wasm_only_externs>()?;
+            Ok(Opt::WasmOnlyExterns)
+        } else if l.peek(kw::enum_str) {
+            input.parse::<kw::enum_str>()?;
+            Ok(Opt::EnumStr)
+        } else if l.peek(kw::import_module_aliases) {
+            input.parse::<kw::import_module_aliases>()?;
+            input.parse::<Token![:]>()?;
+            let contents;
+            syn::braced!(contents in input);
+            let mut aliases = Vec::new();
+            while !contents.is_empty() {
+                let from = contents.parse::<syn::LitStr>()?;
+                contents.parse::<Token![:]>()?;
+                let to = contents.parse::<syn::LitStr>()?;
+                aliases.push((from.value(), to.value()));
+                if contents.is_empty() {
+                    break;
+                }
+                contents.parse::<Token![,]>()?;
+            }
+            Ok(Opt::ImportModuleAliases(aliases))
+        } else if l.peek(kw::unsafe_module) {
+            input.parse::<kw::unsafe_module>()?;
+            Ok(Opt::UnsafeModule)
+        } else if l.peek(kw::c_tagged_variants) {
+            input.parse::<kw::c_tagged_variants>()?;
+            Ok(Opt::CTaggedVariants)
+        } else if l.peek(kw::preserve_wit_case) {
+            input.parse::<kw::preserve_wit_case>()?;
+            Ok(Opt::PreserveWitCase)
+        } else if l.peek(kw::box_large_variants) {
+            input.parse::<kw::box_large_variants>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::BoxLargeVariants(input.parse()?))
+        } else if l.peek(kw::doc_abi_details) {
+            input.parse::<kw::doc_abi_details>()?;
+            Ok(Opt::DocAbiDetails)
+        } else if l.peek(kw::split_raw_wrappers) {
+            input.parse::<kw::split_raw_wrappers>()?;
+            Ok(Opt::SplitRawWrappers)
+        } else if l.peek(kw::debug_list_bounds) {
+            input.parse::<kw::debug_list_bounds>()?;
+            Ok(Opt::DebugListBounds)
+        } else if l.peek(kw::type_attribute_macros) {
+            input.parse::<kw::type_attribute_macros>()?;
+            input.parse::<Token![:]>()?;
+            let contents;
+            syn::bracketed!(contents in input);
+            let list = Punctuated::<_, Token![,]>::parse_terminated(&contents)?;
+            Ok(Opt::TypeAttributeMacros(list.iter().cloned().collect()))
+        } else if l.peek(kw::export_context_type) {
+            input.parse::<kw::export_context_type>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::ExportContextType(input.parse()?))
+        } else if l.peek(kw::record_align_overrides) {
+            input.parse::<kw::record_align_overrides>()?;
+            input.parse::<Token![:]>()?;
+            let contents;
+            syn::braced!(contents in input);
+            let mut aligns = Vec::new();
+            while !contents.is_empty() {
+                let name = contents.parse::<syn::LitStr>()?;
+                contents.parse::<Token![:]>()?;
+                let align = contents.parse::<syn::LitInt>()?;
+                aligns.push((name.value(), align.base10_parse()?));
+                if contents.is_empty() {
+                    break;
+                }
+                contents.parse::<Token![,]>()?;
+            }
+            Ok(Opt::RecordAlignOverrides(aligns))
+        } else if l.peek(kw::newtype_list_aliases) {
+            input.parse::<kw::newtype_list_aliases>()?;
+            Ok(Opt::NewtypeListAliases)
+        } else if l.peek(kw::type_name_prefix) {
+            input.parse::<kw::type_name_prefix>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::TypeNamePrefix(input.parse()?))
+        } else if l.peek(kw::cold_trap_fns) {
+            input.parse::<kw::cold_trap_fns>()?;
+            Ok(Opt::ColdTrapFns)
+        } else if l.peek(kw::abi_version_const) {
+            input.parse::<kw::abi_version_const>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::AbiVersionConst(input.parse()?))
+        } else if l.peek(kw::string_encoding) {
+            input.parse::<kw::string_encoding>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::StringEncoding(input.parse()?))
+        } else if l.peek(kw::list_params_impl_intoiterator) {
+            input.parse::<kw::list_params_impl_intoiterator>()?;
+            Ok(Opt::ListParamsImplIntoiterator)
+        } else if l.peek(kw::variant_accessors) {
+            input.parse::<kw::variant_accessors>()?;
+            Ok(Opt::VariantAccessors)
+        } else if l.peek(kw::shared_import_return_area) {
+            input.parse::<kw::shared_import_return_area>()?;
+            Ok(Opt::SharedImportReturnArea)
+        } else if l.peek(kw::enum_name_str) {
+            input.parse::<kw::enum_name_str>()?;
+            Ok(Opt::EnumNameStr)
+        } else if l.peek(kw::emit_name_consts) {
+            input.parse::<kw::emit_name_consts>()?;
+            Ok(Opt::EmitNameConsts)
+        } else if l.peek(kw::inline_list_capacity) {
+            input.parse::<kw::inline_list_capacity>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::InlineListCapacity(input.parse()?))
+        } else if l.peek(kw::borrowed_export_params) {
+            input.parse::<kw::borrowed_export_params>()?;
+            Ok(Opt::BorrowedExportParams)
+        } else if l.peek(kw::generated_header) {
+            input.parse::<kw::generated_header>()?;
+            input.parse::<Token![:]>()?;
+            Ok(Opt::GeneratedHeader(input.parse()?))
         } else {
             Err(l.error())
         }
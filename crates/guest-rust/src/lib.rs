@@ -9,12 +9,64 @@ pub use wit_bindgen_guest_rust_macro::*;
 #[doc(hidden)]
 pub use bitflags;
 
+/// The Rust type of a WIT `result` with neither an `ok` nor an `err` payload
+/// when bindings are generated with `unit_result_as_status`.
+///
+/// This is represented the same way as `Result<(), ()>` in the canonical ABI.
+#[must_use]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Status {
+    Ok,
+    Err,
+}
+
+impl Status {
+    /// Returns `true` if this is `Status::Ok`.
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Status::Ok)
+    }
+
+    /// Returns `true` if this is `Status::Err`.
+    pub fn is_err(&self) -> bool {
+        matches!(self, Status::Err)
+    }
+}
+
+impl From<Status> for Result<(), ()> {
+    fn from(status: Status) -> Result<(), ()> {
+        match status {
+            Status::Ok => Ok(()),
+            Status::Err => Err(()),
+        }
+    }
+}
+
+impl From<Result<(), ()>> for Status {
+    fn from(result: Result<(), ()>) -> Status {
+        match result {
+            Ok(()) => Status::Ok,
+            Err(()) => Status::Err,
+        }
+    }
+}
+
+/// The error returned by the `FromStr` impls generated for enums with
+/// `enum_str` when a string isn't the name of any case.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseEnumError;
+
+impl core::fmt::Display for ParseEnumError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("unknown enum case")
+    }
+}
+
 #[doc(hidden)]
 pub mod rt {
     use super::alloc::alloc::Layout;
 
     // Re-export things from liballoc for convenient use.
-    pub use super::alloc::{alloc, string, vec};
+    pub use super::alloc::{alloc, boxed, string, vec};
 
     #[cfg(feature = "realloc")]
     #[no_mangle]
@@ -60,6 +112,105 @@ pub mod rt {
         alloc::dealloc(ptr as *mut u8, layout);
     }
 
+    /// Checks that the `len` elements of `size` bytes at `ptr` lie within
+    /// linear memory, panicking otherwise in debug builds.
+    ///
+    /// This is called when lifting lists with `debug_list_bounds`.
+    pub fn check_list_bounds(ptr: i32, len: usize, size: usize) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        #[cfg(target_arch = "wasm32")]
+        let memory = core::arch::wasm32::memory_size(0)
+            .checked_mul(65536)
+            .unwrap_or(usize::MAX);
+        #[cfg(not(target_arch = "wasm32"))]
+        let memory = usize::MAX;
+        let end = len
+            .checked_mul(size)
+            .and_then(|bytes| (ptr as u32 as usize).checked_add(bytes));
+        match end {
+            Some(end) if end <= memory => {}
+            _ => panic!("list of {len} elements of {size} bytes at {ptr:#x} is out of bounds"),
+        }
+    }
+
+    /// Panics because an invalid discriminant of a `kind` was lifted.
+    ///
+    /// This is called when lifting with `cold_trap_fns`, keeping the panic
+    /// out of line from the code lifting valid values.
+    #[cold]
+    #[inline(never)]
+    pub fn invalid_discriminant(kind: &str) -> ! {
+        panic!("invalid {kind} discriminant")
+    }
+
+    /// The items of a list passed to an import as an `impl IntoIterator`,
+    /// which knows up front how many it will yield so that the list can be
+    /// lowered in place.
+    ///
+    /// Iterators with an exact size hint, which includes every
+    /// `ExactSizeIterator`, are used directly while others are collected
+    /// into a `Vec` first.
+    pub struct SizedIter<I: Iterator> {
+        len: usize,
+        items: SizedIterItems<I>,
+    }
+
+    enum SizedIterItems<I: Iterator> {
+        Exact(I),
+        Collected(vec::IntoIter<I::Item>),
+    }
+
+    impl<I: Iterator> SizedIter<I> {
+        pub fn new(items: impl IntoIterator<IntoIter = I>) -> SizedIter<I> {
+            let items = items.into_iter();
+            match items.size_hint() {
+                (lo, Some(hi)) if lo == hi => SizedIter {
+                    len: lo,
+                    items: SizedIterItems::Exact(items),
+                },
+                _ => {
+                    let items = items.collect::<vec::Vec<_>>();
+                    SizedIter {
+                        len: items.len(),
+                        items: SizedIterItems::Collected(items.into_iter()),
+                    }
+                }
+            }
+        }
+    }
+
+    impl<I: Iterator> Iterator for SizedIter<I> {
+        type Item = I::Item;
+
+        fn next(&mut self) -> Option<I::Item> {
+            if self.len == 0 {
+                return None;
+            }
+            self.len -= 1;
+            let item = match &mut self.items {
+                SizedIterItems::Exact(items) => items.next(),
+                SizedIterItems::Collected(items) => items.next(),
+            };
+            // Stopping early would leave the rest of the lowered list
+            // uninitialized, so an iterator with a wrong size hint panics.
+            Some(item.expect("iterator yielded fewer items than its size hint"))
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (self.len, Some(self.len))
+        }
+    }
+
+    impl<I: Iterator> ExactSizeIterator for SizedIter<I> {}
+
+    /// Borrows the contents of a boxed variant payload, whether the payload
+    /// was matched by value or by reference.
+    pub fn unbox<T: ?Sized>(b: &boxed::Box<T>) -> &T {
+        b
+    }
+
     macro_rules! as_traits {
         ($(($trait_:ident $func:ident $ty:ident <=> $($tys:ident)*))*) => ($(
             pub fn $func<T: $trait_>(t: T) -> $ty {
@@ -0,0 +1,64 @@
+//! Bindings generated with `no_std` and `std_feature`, checked to compile in
+//! a `#![no_std]` crate which only has `alloc`, and with its `std` feature.
+
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+wit_bindgen_guest_rust::generate!({
+    inline: "
+        default world baz {
+            import imports: interface {
+                record failure {
+                    message: string,
+                    codes: list<u32>,
+                }
+
+                foo: func(name: string) -> result<list<string>, failure>
+            }
+
+            export exports: interface {
+                record failure {
+                    message: string,
+                }
+
+                bar: func(names: list<string>) -> result<string, failure>
+            }
+
+            import names: func() -> list<string>
+        }
+    ",
+    no_std,
+    std_feature,
+});
+
+struct Component;
+
+impl exports::Exports for Component {
+    fn bar(names: Vec<String>) -> Result<String, exports::Failure> {
+        names.into_iter().next().ok_or(exports::Failure {
+            message: String::from("no names"),
+        })
+    }
+}
+
+export_baz!(Component);
+
+pub fn foo() -> String {
+    let names: Vec<String> = names();
+    match imports::foo(&names.concat()) {
+        Ok(names) => names.concat(),
+        // `Display` is available regardless of the `std` feature.
+        Err(e) => alloc::format!("{e}"),
+    }
+}
+
+#[cfg(feature = "std")]
+pub fn error(e: imports::Failure) -> std::boxed::Box<dyn std::error::Error> {
+    std::boxed::Box::new(e)
+}
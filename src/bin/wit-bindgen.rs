@@ -174,8 +174,7 @@ fn gen_world(
                 .ok_or_else(|| anyhow!("no default world in document"))?
         }
     };
-    generator.generate(&resolve, world, files);
-    Ok(())
+    generator.generate(&resolve, world, files)
 }
 
 #[test]
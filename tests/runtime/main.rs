@@ -13,6 +13,7 @@ mod lists;
 mod many_arguments;
 mod numbers;
 mod records;
+mod results;
 mod smoke;
 mod strings;
 mod unions;
@@ -142,7 +143,7 @@ fn tests(name: &str) -> Result<Vec<PathBuf>> {
                 opts.string_encoding = wit_component::StringEncoding::UTF16;
             }
         }
-        opts.build().generate(&resolve, world, &mut files);
+        opts.build().generate(&resolve, world, &mut files).unwrap();
 
         for (file, contents) in files.iter() {
             let dst = out_dir.join(file);
@@ -218,7 +219,8 @@ fn tests(name: &str) -> Result<Vec<PathBuf>> {
 
         wit_bindgen_gen_guest_teavm_java::Opts::default()
             .build()
-            .generate(&resolve, world, &mut files);
+            .generate(&resolve, world, &mut files)
+            .unwrap();
 
         let package_dir = java_dir.join(&format!("wit_{}", world_name));
         fs::create_dir_all(&package_dir).unwrap();
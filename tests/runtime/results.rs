@@ -0,0 +1,124 @@
+use anyhow::Result;
+use wasmtime::Store;
+
+wasmtime::component::bindgen!("world" in "tests/runtime/results");
+
+#[derive(Default)]
+pub struct MyImports;
+
+impl imports::Imports for MyImports {
+    fn string_error(&mut self, a: f32) -> Result<Result<f32, String>> {
+        Ok(if a == 0.0 {
+            Err("zero".to_string())
+        } else {
+            Ok(a)
+        })
+    }
+
+    fn enum_error(&mut self, a: f64) -> Result<Result<f64, imports::E>> {
+        Ok(if a == 0.0 { Err(imports::E::A) } else { Ok(a) })
+    }
+
+    fn record_error(&mut self, a: f64) -> Result<Result<f64, imports::E2>> {
+        Ok(if a == 0.0 {
+            Err(imports::E2 {
+                line: 420,
+                column: 0,
+            })
+        } else {
+            Ok(a)
+        })
+    }
+
+    fn variant_error(&mut self, a: f64) -> Result<Result<f64, imports::E3>> {
+        Ok(if a == 0.0 {
+            Err(imports::E3::E2(imports::E2 {
+                line: 420,
+                column: 0,
+            }))
+        } else if a == 1.0 {
+            Err(imports::E3::E1(imports::E::B))
+        } else {
+            Ok(a)
+        })
+    }
+
+    fn empty_error(&mut self, a: u32) -> Result<Result<u32, ()>> {
+        Ok(if a == 0 { Err(()) } else { Ok(a) })
+    }
+
+    fn nested_error(&mut self, a: u32) -> Result<Result<Result<String, u8>, String>> {
+        Ok(match a {
+            0 => Ok(Ok("inner ok".to_string())),
+            1 => Ok(Err(7)),
+            _ => Err("outer error".to_string()),
+        })
+    }
+}
+
+#[test]
+fn run() -> Result<()> {
+    crate::run_test(
+        "results",
+        |linker| Results::add_to_linker(linker, |x| &mut x.0),
+        |store, component, linker| Results::instantiate(store, component, linker),
+        run_test,
+    )
+}
+
+fn run_test(exports: Results, store: &mut Store<crate::Wasi<MyImports>>) -> Result<()> {
+    use exports::*;
+
+    let exports = exports.exports();
+
+    assert_eq!(
+        exports.call_string_error(&mut *store, 0.0)?,
+        Err("zero".to_string())
+    );
+    assert_eq!(exports.call_string_error(&mut *store, 1.0)?, Ok(1.0));
+
+    assert!(matches!(
+        exports.call_enum_error(&mut *store, 0.0)?,
+        Err(E::A)
+    ));
+    assert!(matches!(exports.call_enum_error(&mut *store, 1.0)?, Ok(b) if b == 1.0));
+
+    assert!(matches!(
+        exports.call_record_error(&mut *store, 0.0)?,
+        Err(E2 {
+            line: 420,
+            column: 0
+        })
+    ));
+    assert!(matches!(exports.call_record_error(&mut *store, 1.0)?, Ok(b) if b == 1.0));
+
+    assert!(matches!(
+        exports.call_variant_error(&mut *store, 0.0)?,
+        Err(E3::E2(E2 {
+            line: 420,
+            column: 0
+        }))
+    ));
+    assert!(matches!(
+        exports.call_variant_error(&mut *store, 1.0)?,
+        Err(E3::E1(E::B))
+    ));
+    assert!(matches!(exports.call_variant_error(&mut *store, 2.0)?, Ok(b) if b == 2.0));
+
+    assert_eq!(exports.call_empty_error(&mut *store, 0)?, Err(()));
+    assert_eq!(exports.call_empty_error(&mut *store, 1)?, Ok(1));
+
+    // Each of the three outcomes of a nested result survives the round trip
+    // through the guest's import and export.
+    assert_eq!(
+        exports.call_nested_error(&mut *store, 0)?,
+        Ok(Ok("inner ok".to_string()))
+    );
+    assert_eq!(exports.call_nested_error(&mut *store, 1)?, Ok(Err(7)));
+    assert_eq!(
+        exports.call_nested_error(&mut *store, 2)?,
+        Err("outer error".to_string())
+    );
+
+    Ok(())
+}
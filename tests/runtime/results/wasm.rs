@@ -37,4 +37,12 @@ impl exports::Exports for Exports {
     fn empty_error(a: u32) -> Result<u32, ()> {
         imports::empty_error(a)
     }
+
+    fn nested_error(a: u32) -> Result<Result<String, u8>, String> {
+        match imports::nested_error(a) {
+            Ok(Ok(s)) => Ok(Ok(s)),
+            Ok(Err(e)) => Ok(Err(e)),
+            Err(s) => Err(s),
+        }
+    }
 }